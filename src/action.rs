@@ -0,0 +1,140 @@
+use crate::BlackjackGameError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// A playing decision the player can make on their hand. The options some variants use in place of hitting, standing,
+/// doubling, splitting and surrendering share the key of the standard option, as a hand is never offered both
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Stand,
+    Hit,
+    Split,
+    DoubleDown,
+    Surrender,
+    /// Spanish 21, surrender a doubled hand forfeiting the original bet
+    Rescue,
+    /// Blackjack Switch, switch the second cards of the two hands
+    Switch,
+    /// Blackjack Switch, keep the hands as they were dealt
+    Keep,
+    /// Free Bet Blackjack, double down with the house funding the double
+    FreeDouble,
+    /// Free Bet Blackjack, split with the house funding the second hand
+    FreeSplit,
+    /// Pontoon, take another card
+    Twist,
+    /// Pontoon, stand on the hand
+    Stick,
+    /// Pontoon, raise the bet by the original bet and take another card
+    Buy,
+    /// Take insurance of half the bet against the dealers ace, paying 2:1 if the dealer has blackjack
+    Insurance,
+    /// Decline insurance against the dealers ace
+    NoInsurance,
+}
+
+/// Every action, in the order they are listed when looking one up
+const ACTIONS: [Action; 15] = [
+    Action::Stand,
+    Action::Hit,
+    Action::Split,
+    Action::DoubleDown,
+    Action::Surrender,
+    Action::Rescue,
+    Action::Switch,
+    Action::Keep,
+    Action::FreeDouble,
+    Action::FreeSplit,
+    Action::Twist,
+    Action::Stick,
+    Action::Buy,
+    Action::Insurance,
+    Action::NoInsurance,
+];
+
+impl Action {
+    /// Returns the name of the action, as it is listed to the player and recorded in the hand history
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Stand => "stand",
+            Action::Hit => "hit",
+            Action::Split => "split",
+            Action::DoubleDown => "double down",
+            Action::Surrender => "surrender",
+            Action::Rescue => "rescue",
+            Action::Switch => "switch",
+            Action::Keep => "keep",
+            Action::FreeDouble => "free double",
+            Action::FreeSplit => "free split",
+            Action::Twist => "twist",
+            Action::Stick => "stick",
+            Action::Buy => "buy",
+            Action::Insurance => "insurance",
+            Action::NoInsurance => "no insurance",
+        }
+    }
+
+    /// Returns the key that plays the action
+    pub fn key(&self) -> char {
+        match self {
+            Action::Hit | Action::Twist => 'h',
+            Action::Stand | Action::Stick => 's',
+            Action::DoubleDown | Action::FreeDouble => 'd',
+            Action::Split | Action::FreeSplit => 'p',
+            Action::Surrender | Action::Rescue => 'r',
+            Action::Switch => 'w',
+            Action::Keep => 'k',
+            Action::Buy => 'b',
+            Action::Insurance => 'i',
+            Action::NoInsurance => 'n',
+        }
+    }
+}
+
+/// Returns the number of the option in `options` the player chose by entering `input`, either the options number, its
+/// key or its name. Returns None if `input` does not choose any of the options
+pub fn choose_option(options: &HashMap<i32, Action>, input: &str) -> Option<i32> {
+    let input = input.trim().to_lowercase();
+    if let Ok(n) = i32::from_str(&input) {
+        return options.contains_key(&n).then_some(n);
+    }
+    options
+        .iter()
+        .find(|(_, action)| {
+            input == action.name() || input.chars().eq(std::iter::once(action.key()))
+        })
+        .map(|(n, _)| *n)
+}
+
+impl FromStr for Action {
+    type Err = BlackjackGameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ACTIONS
+            .into_iter()
+            .find(|action| action.name() == s)
+            .ok_or_else(|| BlackjackGameError::new(format!("{s} is not a valid action")))
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Actions are recorded in the hand history by name
+impl Serialize for Action {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Action {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Action::from_str(&name).map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,479 @@
+use crate::BlackjackGameError;
+use clap::ValueEnum;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The bets of the 1-3-2-6 system in units, one for each win in a row
+const ONE_THREE_TWO_SIX: [u32; 4] = [1, 3, 2, 6];
+/// The number of wins in a row a Paroli progression lets ride before returning to one unit
+const PAROLI_WINS: usize = 3;
+/// The players advantage gained for every point of the Hi-Lo true count, roughly half a percent
+pub const ADVANTAGE_PER_TRUE_COUNT: f32 = 0.005;
+/// The variance of the result of a round of blackjack in squared bets, i.e. a standard deviation of about 1.15 bets
+pub const ROUND_VARIANCE: f32 = 1.33;
+
+/// Returns `desired`, the bet a betting scheme calls for, brought within the tables `limits`, the minimum and maximum
+/// bet, and no larger than `balance`. Returns None if `balance` cannot cover the minimum bet
+fn limit_bet(desired: u32, limits: (u32, Option<u32>), balance: f32) -> Option<u32> {
+    let (minimum, maximum) = limits;
+    if balance < minimum as f32 {
+        return None;
+    }
+    let affordable = f32::floor(balance) as u32;
+    let maximum = maximum.map_or(affordable, |max| u32::min(max, affordable));
+    Some(desired.clamp(minimum, maximum))
+}
+
+/// A betting system, deciding how many units to bet on the next round from the results of the previous rounds
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum BettingSystem {
+    /// Bet one unit every round
+    Flat,
+    /// Double the bet after every loss, returning to one unit after a win
+    Martingale,
+    /// Reverse Martingale, double the bet after every win for up to three wins, returning to one unit after a loss
+    Paroli,
+    /// Raise the bet by one unit after a loss and lower it by one unit after a win
+    #[value(name = "dalembert")]
+    DAlembert,
+    /// Move one step along the Fibonacci sequence after a loss and back two steps after a win
+    Fibonacci,
+    /// Raise the bet by one unit after a win until the cycle is one unit up, keeping the bet after a loss
+    OscarsGrind,
+    /// Bet 1, 3, 2 then 6 units over a run of wins, returning to one unit after a loss or the fourth win
+    #[value(name = "1-3-2-6")]
+    OneThreeTwoSix,
+}
+
+/// Every betting system, in the order they are reported
+pub const BETTING_SYSTEMS: [BettingSystem; 7] = [
+    BettingSystem::Flat,
+    BettingSystem::Martingale,
+    BettingSystem::Paroli,
+    BettingSystem::DAlembert,
+    BettingSystem::Fibonacci,
+    BettingSystem::OscarsGrind,
+    BettingSystem::OneThreeTwoSix,
+];
+
+impl Display for BettingSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BettingSystem::Flat => "Flat",
+            BettingSystem::Martingale => "Martingale",
+            BettingSystem::Paroli => "Paroli",
+            BettingSystem::DAlembert => "D'Alembert",
+            BettingSystem::Fibonacci => "Fibonacci",
+            BettingSystem::OscarsGrind => "Oscar's Grind",
+            BettingSystem::OneThreeTwoSix => "1-3-2-6",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Returns the `n`th number of the Fibonacci sequence 1, 1, 2, 3, 5, ..., saturating rather than overflowing
+fn fibonacci(n: usize) -> u32 {
+    let (mut a, mut b) = (1u32, 1u32);
+    for _ in 0..n {
+        (a, b) = (b, a.saturating_add(b));
+    }
+    a
+}
+
+/// A betting system being played, i.e. the system along with its unit and its place in the progression
+#[derive(Clone, Debug, PartialEq)]
+pub struct BettingStrategy {
+    system: BettingSystem,
+    unit: u32,
+    /// The next bet of the progression in units, before any table limit is applied
+    units: u32,
+    /// The place in the progression, i.e. the step of the Fibonacci sequence, the Paroli win streak or the 1-3-2-6 step
+    step: usize,
+    /// Oscar's Grind, the units won or lost so far in the current cycle
+    cycle: f32,
+}
+
+impl BettingStrategy {
+    /// Starts playing `system` with bets of `unit` dollars per unit. The method panics if `unit` is zero
+    pub fn new(system: BettingSystem, unit: u32) -> BettingStrategy {
+        assert!(unit > 0, "The betting unit must be at least $1");
+        BettingStrategy {
+            system,
+            unit,
+            units: 1,
+            step: 0,
+            cycle: 0.0,
+        }
+    }
+
+    /// Returns the betting system being played
+    pub fn system(&self) -> BettingSystem {
+        self.system
+    }
+
+    /// Returns the size of a unit in dollars
+    pub fn unit(&self) -> u32 {
+        self.unit
+    }
+
+    /// Returns the bet the progression calls for next, before any table limit is applied
+    pub fn desired_bet(&self) -> u32 {
+        self.units.saturating_mul(self.unit)
+    }
+
+    /// Returns the bet to place next, i.e. the bet the progression calls for brought within the tables `limits`, the
+    /// minimum and maximum bet, and no larger than `balance`. Returns None if `balance` cannot cover the minimum bet.
+    /// A progression that calls for more than the limits allow is capped, it does not reset
+    pub fn next_bet(&self, limits: (u32, Option<u32>), balance: f32) -> Option<u32> {
+        limit_bet(self.desired_bet(), limits, balance)
+    }
+
+    /// Moves the progression on after a round that won `net` dollars, negative if the round was lost. Only whether the
+    /// round was won, lost or pushed matters, other than to Oscar's Grind which counts the units won over a cycle
+    pub fn record_result(&mut self, net: f32) {
+        let won = net > 0.0;
+        let lost = net < 0.0;
+        match self.system {
+            BettingSystem::Flat => {}
+            BettingSystem::Martingale if won => self.units = 1,
+            BettingSystem::Martingale if lost => self.units = self.units.saturating_mul(2),
+            BettingSystem::Paroli if won => {
+                self.step += 1;
+                if self.step == PAROLI_WINS {
+                    self.reset();
+                } else {
+                    self.units = self.units.saturating_mul(2);
+                }
+            }
+            BettingSystem::Paroli if lost => self.reset(),
+            BettingSystem::DAlembert if won => self.units = u32::max(1, self.units - 1),
+            BettingSystem::DAlembert if lost => self.units = self.units.saturating_add(1),
+            BettingSystem::Fibonacci if won || lost => {
+                self.step = if won {
+                    self.step.saturating_sub(2)
+                } else {
+                    self.step + 1
+                };
+                self.units = fibonacci(self.step);
+            }
+            BettingSystem::OscarsGrind => {
+                self.cycle += net / self.unit as f32;
+                if self.cycle >= 1.0 {
+                    self.reset();
+                } else if won {
+                    // Never bet more than it takes to finish the cycle one unit up
+                    let to_finish = f32::ceil(1.0 - self.cycle) as u32;
+                    self.units = u32::max(1, u32::min(self.units + 1, to_finish));
+                }
+            }
+            BettingSystem::OneThreeTwoSix if won => {
+                self.step = (self.step + 1) % ONE_THREE_TWO_SIX.len();
+                self.units = ONE_THREE_TWO_SIX[self.step];
+            }
+            BettingSystem::OneThreeTwoSix if lost => self.reset(),
+            // A push leaves every other progression where it was
+            _ => {}
+        }
+    }
+
+    /// Returns the progression to its first bet of one unit
+    pub fn reset(&mut self) {
+        self.units = 1;
+        self.step = 0;
+        self.cycle = 0.0;
+    }
+}
+
+/// A bet ramp, the units bet at each true count. Below the lowest true count of the ramp one unit is bet
+#[derive(Clone, Debug, PartialEq)]
+pub struct BetRamp {
+    /// The true counts, in increasing order, from which each number of units is bet
+    steps: Vec<(i32, u32)>,
+}
+
+impl BetRamp {
+    /// Returns a ramp spreading bets from 1 to `max_units` units, two units for every true count above 1, e.g. a spread
+    /// of 8 bets 2 units at a true count of 2, 4 at 3, 6 at 4 and 8 from 5. The method panics if `max_units` is zero
+    pub fn spread(max_units: u32) -> BetRamp {
+        assert!(max_units > 0, "A bet ramp must bet at least one unit");
+        let mut steps = vec![];
+        let mut true_count = 2;
+        while steps.last().map_or(1, |(_, units)| *units) < max_units {
+            steps.push((true_count, u32::min(max_units, 2 * (true_count as u32 - 1))));
+            true_count += 1;
+        }
+        BetRamp { steps }
+    }
+
+    /// Returns the units bet at `true_count`, which is rounded down to a whole count
+    pub fn units(&self, true_count: f32) -> u32 {
+        let true_count = f32::floor(true_count) as i32;
+        self.steps
+            .iter()
+            .rev()
+            .find(|(from, _)| true_count >= *from)
+            .map_or(1, |(_, units)| *units)
+    }
+
+    /// Returns the largest number of units the ramp bets
+    pub fn max_units(&self) -> u32 {
+        self.steps
+            .iter()
+            .map(|(_, units)| *units)
+            .max()
+            .unwrap_or(1)
+    }
+}
+
+impl FromStr for BetRamp {
+    type Err = BlackjackGameError;
+
+    /// Parses a ramp from comma separated pairs of a true count and the units bet from that count, e.g. `"2:2,3:4,4:8"`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            BlackjackGameError::new(format!(
+                "{s} is not a valid bet ramp, expected pairs of a true count and units such as 2:2,3:4,4:8"
+            ))
+        };
+        let mut steps: Vec<(i32, u32)> = s
+            .split(',')
+            .map(|step| {
+                let (true_count, units) = step.trim().split_once(':').ok_or_else(invalid)?;
+                let true_count = i32::from_str(true_count.trim()).map_err(|_| invalid())?;
+                let units = u32::from_str(units.trim()).map_err(|_| invalid())?;
+                if units == 0 {
+                    return Err(invalid());
+                }
+                Ok((true_count, units))
+            })
+            .collect::<Result<_, _>>()?;
+        steps.sort_by_key(|(true_count, _)| *true_count);
+        if steps.windows(2).any(|w| w[0].0 == w[1].0) {
+            return Err(invalid());
+        }
+        Ok(BetRamp { steps })
+    }
+}
+
+/// How a bot sizes its bets, either with a progression on the results of the previous rounds or from the true count
+#[derive(Clone, Debug, PartialEq)]
+pub enum BetSizing {
+    Progression(BettingStrategy),
+    /// Bets the units of `ramp` for the true count, in units of `unit` dollars
+    Ramp {
+        ramp: BetRamp,
+        unit: u32,
+    },
+    /// Bets `fraction` of the Kelly bet, i.e. of the balance times the advantage over the variance of a round. The
+    /// advantage is estimated as `edge`, the advantage off the top of the shoe, plus half a percent per true count
+    Kelly {
+        fraction: f32,
+        edge: f32,
+    },
+}
+
+impl BetSizing {
+    /// Returns the players estimated advantage at `true_count`, as a fraction of the bet
+    pub fn advantage(edge: f32, true_count: f32) -> f32 {
+        edge + ADVANTAGE_PER_TRUE_COUNT * true_count
+    }
+
+    /// Returns true if every bet is the same, i.e. flat betting
+    pub fn is_flat(&self) -> bool {
+        matches!(self, BetSizing::Progression(strategy) if strategy.system() == BettingSystem::Flat)
+    }
+
+    /// Returns the bet called for at `true_count` with a balance of `balance`, before any table limit is applied
+    pub fn desired_bet(&self, true_count: f32, balance: f32) -> u32 {
+        match self {
+            BetSizing::Progression(strategy) => strategy.desired_bet(),
+            BetSizing::Ramp { ramp, unit } => ramp.units(true_count).saturating_mul(*unit),
+            // Without an advantage Kelly bets nothing, which the table minimum turns into the smallest bet allowed
+            BetSizing::Kelly { fraction, edge } => {
+                let advantage = Self::advantage(*edge, true_count);
+                f32::floor(f32::max(
+                    0.0,
+                    fraction * balance * advantage / ROUND_VARIANCE,
+                )) as u32
+            }
+        }
+    }
+
+    /// Returns the bet to place next at `true_count`, brought within the tables `limits` and no larger than `balance`.
+    /// Returns None if `balance` cannot cover the minimum bet
+    pub fn next_bet(
+        &self,
+        limits: (u32, Option<u32>),
+        balance: f32,
+        true_count: f32,
+    ) -> Option<u32> {
+        limit_bet(self.desired_bet(true_count, balance), limits, balance)
+    }
+
+    /// Moves a progression on after a round that won `net` dollars, bets sized from the count do not depend on the
+    /// previous rounds
+    pub fn record_result(&mut self, net: f32) {
+        if let BetSizing::Progression(strategy) = self {
+            strategy.record_result(net);
+        }
+    }
+}
+
+impl Display for BetSizing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BetSizing::Progression(strategy) => write!(f, "{}", strategy.system()),
+            BetSizing::Ramp { ramp, .. } => write!(f, "Ramp 1-{}", ramp.max_units()),
+            BetSizing::Kelly { fraction, .. } => write!(f, "Kelly {:.0}%", 100.0 * fraction),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: f32 = 1.0;
+    const L: f32 = -1.0;
+    const P: f32 = 0.0;
+
+    /// Plays `system` with a $5 unit over rounds with the `results` given in bets, e.g. 1.0 for a win, and returns the
+    /// units bet on each round followed by the units the progression calls for next
+    fn units_bet(system: BettingSystem, results: &[f32]) -> Vec<u32> {
+        let mut strategy = BettingStrategy::new(system, 5);
+        let mut units = vec![];
+        for result in results {
+            let bet = strategy.desired_bet();
+            units.push(bet / 5);
+            strategy.record_result(result * bet as f32);
+        }
+        units.push(strategy.desired_bet() / 5);
+        units
+    }
+
+    #[test]
+    fn fibonacci_sequence() {
+        let sequence: Vec<u32> = (0..8).map(fibonacci).collect();
+        assert_eq!(sequence, vec![1, 1, 2, 3, 5, 8, 13, 21]);
+        assert_eq!(fibonacci(100), u32::MAX);
+    }
+
+    #[test]
+    fn flat_and_martingale() {
+        assert_eq!(units_bet(BettingSystem::Flat, &[L, W, L]), vec![1, 1, 1, 1]);
+        assert_eq!(
+            units_bet(BettingSystem::Martingale, &[L, L, L, W, L, P]),
+            vec![1, 2, 4, 8, 1, 2, 2]
+        );
+    }
+
+    #[test]
+    fn paroli_resets_after_three_wins() {
+        assert_eq!(
+            units_bet(BettingSystem::Paroli, &[W, W, W, W, L]),
+            vec![1, 2, 4, 1, 2, 1]
+        );
+    }
+
+    #[test]
+    fn dalembert_never_bets_below_one_unit() {
+        assert_eq!(
+            units_bet(BettingSystem::DAlembert, &[L, L, W, W, W]),
+            vec![1, 2, 3, 2, 1, 1]
+        );
+    }
+
+    #[test]
+    fn fibonacci_steps_back_two_after_a_win() {
+        assert_eq!(
+            units_bet(BettingSystem::Fibonacci, &[L, L, L, L, W, W]),
+            vec![1, 1, 2, 3, 5, 2, 1]
+        );
+    }
+
+    #[test]
+    fn oscars_grind_caps_the_bet_at_the_cycle_target() {
+        // Three units down, the second win would raise the bet to 3 units but 1 finishes the cycle one unit up
+        assert_eq!(
+            units_bet(BettingSystem::OscarsGrind, &[L, L, L, W, W, W, L]),
+            vec![1, 1, 1, 1, 2, 1, 1, 1]
+        );
+    }
+
+    #[test]
+    fn one_three_two_six_wraps_around_after_the_fourth_win() {
+        assert_eq!(
+            units_bet(BettingSystem::OneThreeTwoSix, &[W, W, W, W, W, L]),
+            vec![1, 3, 2, 6, 1, 3, 1]
+        );
+    }
+
+    #[test]
+    fn bet_ramp_from_str() {
+        let ramp = BetRamp::from_str("4:8, 2:2,3:4").unwrap();
+        assert_eq!(ramp.steps, vec![(2, 2), (3, 4), (4, 8)]);
+        assert_eq!(ramp.max_units(), 8);
+        let units: Vec<u32> = [-3.0, 1.9, 2.0, 3.5, 4.0, 10.0]
+            .into_iter()
+            .map(|tc| ramp.units(tc))
+            .collect();
+        assert_eq!(units, vec![1, 1, 2, 4, 8, 8]);
+        assert_eq!(BetRamp::from_str("-1:2").unwrap().units(-1.5), 1);
+        assert_eq!(BetRamp::from_str("-1:2").unwrap().units(-0.5), 2);
+
+        for invalid in ["", "2", "2:0", "2:2,2:4", "a:2", "2:-1", "2:2;3:4"] {
+            assert!(
+                BetRamp::from_str(invalid).is_err(),
+                "{invalid} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn bet_ramp_spread() {
+        assert_eq!(BetRamp::spread(1).steps, vec![]);
+        assert_eq!(BetRamp::spread(2).steps, vec![(2, 2)]);
+        assert_eq!(
+            BetRamp::spread(8).steps,
+            vec![(2, 2), (3, 4), (4, 6), (5, 8)]
+        );
+        let ramp = BetRamp::spread(12);
+        assert_eq!(ramp.max_units(), 12);
+        assert_eq!(ramp.units(0.0), 1);
+        assert_eq!(ramp.units(6.0), 10);
+        assert_eq!(ramp.units(20.0), 12);
+        assert_eq!(BetRamp::spread(7).steps.last(), Some(&(5, 7)));
+    }
+
+    #[test]
+    fn kelly_bets_a_fraction_of_the_advantage() {
+        let kelly = BetSizing::Kelly {
+            fraction: 0.5,
+            edge: -0.005,
+        };
+        // No advantage off the top of the shoe, so the table minimum is bet
+        assert_eq!(kelly.desired_bet(0.0, 10_000.0), 0);
+        assert_eq!(kelly.next_bet((10, None), 10_000.0, 0.0), Some(10));
+        assert_eq!(kelly.desired_bet(1.0, 10_000.0), 0);
+        // A true count of 3 is an advantage of 1%, half of which over the variance of a round is bet
+        let advantage = BetSizing::advantage(-0.005, 3.0);
+        assert!((advantage - 0.01).abs() < 1e-6);
+        let expected = f32::floor(0.5 * 10_000.0 * advantage / ROUND_VARIANCE) as u32;
+        assert_eq!(kelly.desired_bet(3.0, 10_000.0), expected);
+        assert!((37..=38).contains(&expected));
+        // Kelly bets grow with the balance and the count
+        assert!(kelly.desired_bet(3.0, 20_000.0) > expected);
+        assert!(kelly.desired_bet(5.0, 10_000.0) > expected);
+        assert_eq!(kelly.next_bet((10, Some(25)), 10_000.0, 5.0), Some(25));
+        assert_eq!(kelly.to_string(), "Kelly 50%");
+    }
+
+    #[test]
+    fn limit_bet_clamps_to_the_table_and_balance() {
+        assert_eq!(limit_bet(50, (10, Some(25)), 100.0), Some(25));
+        assert_eq!(limit_bet(1, (10, None), 100.0), Some(10));
+        assert_eq!(limit_bet(50, (10, None), 30.5), Some(30));
+        assert_eq!(limit_bet(50, (10, Some(100)), 40.0), Some(40));
+        assert_eq!(limit_bet(50, (10, None), 9.5), None);
+    }
+}
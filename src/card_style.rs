@@ -0,0 +1,145 @@
+use crate::Card;
+use crossterm::style::Stylize;
+use std::io::IsTerminal;
+
+/// The width of a card drawn as ASCII art, including its border
+const ART_WIDTH: usize = 7;
+
+/// How cards are drawn on the console. The plain style draws cards as they are displayed, e.g. `HQ` and `|*|`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CardStyle {
+    /// Hearts and diamonds are drawn in red
+    pub color: bool,
+    /// Suits are drawn as the symbols ♥♦♣♠ rather than letters
+    pub unicode: bool,
+    /// Cards are drawn as multi-line ASCII-art cards rather than on a single line
+    pub ascii_art: bool,
+}
+
+impl CardStyle {
+    /// Returns the style, or the plain style if the standard output is not a terminal i.e. escape codes and symbols
+    /// would end up in a file or pipe
+    pub fn for_terminal(self) -> CardStyle {
+        if std::io::stdout().is_terminal() {
+            self
+        } else {
+            CardStyle::default()
+        }
+    }
+
+    /// Returns the symbol or letter drawn for `card`'s suit
+    fn suit(&self, card: &Card) -> String {
+        let suit = match (self.unicode, card.suit) {
+            (true, "H") => "♥",
+            (true, "D") => "♦",
+            (true, "C") => "♣",
+            (true, "S") => "♠",
+            (_, suit) => suit,
+        };
+        self.paint(card, suit)
+    }
+
+    /// Returns `text` drawn in the colour of `card`'s suit
+    fn paint(&self, card: &Card, text: &str) -> String {
+        if self.color && (card.suit == "H" || card.suit == "D") {
+            text.red().to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Returns `card` drawn on a single line, the suit followed by the rank as in its `Display`
+    pub fn card(&self, card: &Card) -> String {
+        format!("{}{}", self.suit(card), self.paint(card, card.rank))
+    }
+
+    /// Returns the lines of `cards` drawn side by side, a card that is None is drawn face down.
+    /// Without ASCII art there is a single line with the cards separated by spaces
+    pub fn cards(&self, cards: &[Option<&Card>]) -> Vec<String> {
+        if !self.ascii_art {
+            let cards: Vec<String> = cards
+                .iter()
+                .map(|c| c.map_or_else(Card::display_facedown, |c| self.card(c)))
+                .collect();
+            return vec![cards.join(" ")];
+        }
+
+        let mut lines = vec![String::new(); 5];
+        for (i, card) in cards.iter().enumerate() {
+            let art = match card {
+                Some(card) => {
+                    let rank = self.paint(card, card.rank);
+                    let pad = " ".repeat(ART_WIDTH - 2 - card.rank.len());
+                    [
+                        String::from("+-----+"),
+                        format!("|{rank}{pad}|"),
+                        format!("|  {}  |", self.suit(card)),
+                        format!("|{pad}{rank}|"),
+                        String::from("+-----+"),
+                    ]
+                }
+                None => [
+                    String::from("+-----+"),
+                    String::from("|/////|"),
+                    String::from("|/////|"),
+                    String::from("|/////|"),
+                    String::from("+-----+"),
+                ],
+            };
+            for (line, art_line) in lines.iter_mut().zip(art) {
+                if i > 0 {
+                    line.push(' ');
+                }
+                line.push_str(&art_line);
+            }
+        }
+        lines
+    }
+}
+
+/// Returns the number of columns `text` takes up on the console, i.e. its length without any colour escape codes
+pub fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip the rest of the escape code, which ends with a letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Returns `text` cut down to take up at most `width` columns on the console, keeping its colour escape codes
+pub fn truncate(text: &str, width: usize) -> String {
+    let mut truncated = String::new();
+    let mut columns = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            truncated.push(c);
+            for c in chars.by_ref() {
+                truncated.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else if columns < width {
+            truncated.push(c);
+            columns += 1;
+        }
+    }
+    truncated
+}
+
+/// Returns `text` padded with spaces so it takes up `width` columns on the console
+pub fn pad(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(visible_width(text));
+    format!("{text}{}", " ".repeat(padding))
+}
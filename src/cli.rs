@@ -0,0 +1,318 @@
+use crate::betting::{BetRamp, BetSizing, BettingStrategy, BettingSystem};
+use crate::card_style::CardStyle;
+use crate::config::{ConfigFile, GameConfig, UiMode};
+use crate::history::DEFAULT_HISTORY_PATH;
+use crate::preset::Preset;
+use crate::side_bet::SideBet;
+use crate::BlackjackGameError;
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+
+/// Play blackjack in the terminal. The table is set up from the preset, then the config file, then the arguments given,
+/// each overriding the settings of the last
+#[derive(Parser, Debug)]
+#[command(name = "blackjack_lib", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// A TOML file defining the table, see `ConfigFile`
+    #[arg(long, global = true)]
+    pub config: Option<String>,
+
+    /// The players name, asked for at the start of the game if not given
+    #[arg(long, global = true, value_parser = parse_name)]
+    pub name: Option<String>,
+
+    /// The balance a new player starts with [default: 500]
+    #[arg(long, global = true, value_parser = parse_amount)]
+    pub balance: Option<f32>,
+
+    /// The number of decks in the shoe [default: set by the preset]
+    #[arg(long, global = true, value_parser = clap::value_parser!(u8).range(1..=8))]
+    pub decks: Option<u8>,
+
+    /// The number of passes made when shuffling the shoe [default: 7]
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(1..=100))]
+    pub shuffles: Option<u32>,
+
+    /// The smallest bet allowed on a hand [default: 5]
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(1..))]
+    pub min_bet: Option<u32>,
+
+    /// The largest bet allowed on a hand, there is no maximum if not given
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_bet: Option<u32>,
+
+    /// The bankroll of the table, bets the table could not pay out are refused [default: 500000000]
+    #[arg(long, global = true, value_parser = parse_amount)]
+    pub bankroll: Option<f32>,
+
+    /// The variant, house rules and shoe of the table [default: standard]
+    #[arg(long, global = true, value_enum)]
+    pub preset: Option<Preset>,
+
+    /// A side bet offered at the table with its usual paytable, can be given more than once
+    #[arg(long, global = true, value_enum)]
+    pub side_bet: Vec<SideBet>,
+
+    /// Seeds the shuffle, so the same seed deals the same shoes
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+
+    /// The front-end the game is played with [default: console]
+    #[arg(long, global = true, value_enum)]
+    pub ui: Option<UiMode>,
+
+    /// The file player profiles are loaded from and saved to [default: profiles.json]
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Draw the cards as plain text, without colour or suit symbols
+    #[arg(long, global = true)]
+    pub plain: bool,
+
+    /// Draw the cards without colour
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Draw the suits as letters rather than symbols
+    #[arg(long, global = true)]
+    pub no_unicode: bool,
+
+    /// Draw the cards as multi-line ASCII art
+    #[arg(long, global = true)]
+    pub ascii_art: bool,
+}
+
+/// The commands run instead of playing a game
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Replay every round in a hand history log and report any round the engine no longer reaches the recorded result
+    Replay {
+        /// The JSON Lines hand history log
+        #[arg(default_value_t = format!("{DEFAULT_HISTORY_PATH}.jsonl"))]
+        path: String,
+    },
+    /// Play a game whose first round is dealt from a stacked scenario, or list the scenarios if no name is given
+    Scenario { scenario: Option<String> },
+    /// Simulate a bot playing betting schemes at the table and compare how each fares over the long run, every betting
+    /// system is played if no scheme is given
+    Simulate {
+        /// The number of rounds each scheme plays, fewer if the bot can no longer cover the minimum bet
+        #[arg(long, default_value_t = 1000, value_parser = clap::value_parser!(u32).range(1..))]
+        rounds: u32,
+        #[command(flatten)]
+        betting: BettingArgs,
+    },
+    /// Compute the risk of ruin of the players starting balance for a betting scheme at the table by simulation. Flat
+    /// bets are also given the analytic risk of ruin and the bankroll needed for a target risk of ruin. Bets are flat if
+    /// no scheme is given
+    Risk {
+        /// The risk of ruin in percent to find the bankroll for
+        #[arg(long, default_value_t = 5.0, value_parser = parse_percent)]
+        target: f32,
+        /// The number of sessions simulated
+        #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+        trials: u32,
+        /// The most rounds played in each simulated session
+        #[arg(long, default_value_t = 5000, value_parser = clap::value_parser!(u32).range(1..))]
+        rounds: u32,
+        #[command(flatten)]
+        betting: BettingArgs,
+    },
+}
+
+/// The betting schemes a bot plays
+#[derive(Args, Debug)]
+pub struct BettingArgs {
+    /// A betting system, sizing the bets from the results of the previous rounds
+    #[arg(long, value_enum)]
+    pub system: Option<BettingSystem>,
+
+    /// Spread the bets from 1 unit up to this many units with the true count, two units for every true count above 1
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub spread: Option<u32>,
+
+    /// A bet ramp of true counts and the units bet from each, e.g. 2:2,3:4,4:8
+    #[arg(long, conflicts_with = "spread")]
+    pub ramp: Option<BetRamp>,
+
+    /// Bet this fraction of the Kelly bet for the advantage estimated from the true count, e.g. 0.5 for half Kelly
+    #[arg(long, value_parser = parse_fraction)]
+    pub kelly: Option<f32>,
+
+    /// The estimated advantage in percent off the top of the shoe, which the true count adds half a percent to per point
+    #[arg(long, default_value_t = -0.5, allow_negative_numbers = true)]
+    pub edge: f32,
+
+    /// The size of a betting unit in dollars [default: the minimum bet]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub unit: Option<u32>,
+}
+
+impl BettingArgs {
+    /// Returns the betting schemes given, with units defaulting to `minimum_bet`. If no scheme is given every system of
+    /// `default_systems` is played
+    pub fn bet_sizings(
+        &self,
+        minimum_bet: u32,
+        default_systems: &[BettingSystem],
+    ) -> Vec<BetSizing> {
+        let unit = self.unit.unwrap_or(minimum_bet);
+        let mut sizings = vec![];
+        if let Some(system) = self.system {
+            sizings.push(BetSizing::Progression(BettingStrategy::new(system, unit)));
+        }
+        let ramp = self.ramp.clone().or(self.spread.map(BetRamp::spread));
+        if let Some(ramp) = ramp {
+            sizings.push(BetSizing::Ramp { ramp, unit });
+        }
+        if let Some(fraction) = self.kelly {
+            sizings.push(BetSizing::Kelly {
+                fraction,
+                edge: self.edge / 100.0,
+            });
+        }
+        if sizings.is_empty() {
+            sizings = default_systems
+                .iter()
+                .map(|system| BetSizing::Progression(BettingStrategy::new(*system, unit)))
+                .collect();
+        }
+        sizings
+    }
+}
+
+/// Parses a players name, which can not be blank
+fn parse_name(name: &str) -> Result<String, String> {
+    match name.trim() {
+        "" => Err(String::from("name cannot be empty")),
+        name => Ok(name.to_string()),
+    }
+}
+
+/// Parses a fraction, which must be greater than 0 and at most 1
+fn parse_fraction(fraction: &str) -> Result<f32, String> {
+    match fraction.parse::<f32>() {
+        Ok(f) if f > 0.0 && f <= 1.0 => Ok(f),
+        Ok(_) => Err(format!("{fraction} is not a fraction between 0 and 1")),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Parses a percentage, which must be greater than 0 and less than 100
+fn parse_percent(percent: &str) -> Result<f32, String> {
+    match percent.parse::<f32>() {
+        Ok(p) if p > 0.0 && p < 100.0 => Ok(p),
+        Ok(_) => Err(format!("{percent} is not a percentage between 0 and 100")),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Parses an amount of money, which must be a positive number
+fn parse_amount(amount: &str) -> Result<f32, String> {
+    match amount.parse::<f32>() {
+        Ok(n) if n.is_finite() && n > 0.0 => Ok(n),
+        Ok(_) => Err(format!("{amount} is not a positive amount")),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+impl Cli {
+    /// Returns the configuration of the game given by the preset, config file and arguments, or an error describing the
+    /// file or setting that is invalid
+    pub fn config(&self) -> Result<GameConfig, clap::Error> {
+        let invalid = |e: BlackjackGameError| Cli::command().error(ErrorKind::InvalidValue, e);
+        let file = self
+            .config
+            .as_ref()
+            .map(ConfigFile::load)
+            .transpose()
+            .map_err(invalid)?;
+
+        // Cards are drawn with red suit symbols unless turned off
+        let mut config = GameConfig {
+            card_style: CardStyle {
+                color: true,
+                unicode: true,
+                ascii_art: false,
+            },
+            ..Default::default()
+        };
+        let preset = self
+            .preset
+            .or(file.as_ref().and_then(|f| f.preset()))
+            .unwrap_or_default();
+        preset.apply(&mut config);
+        if let Some(file) = &file {
+            file.apply(&mut config);
+        }
+
+        if self.name.is_some() {
+            config.name = self.name.clone();
+        }
+        if let Some(balance) = self.balance {
+            config.starting_balance = balance;
+        }
+        if let Some(decks) = self.decks {
+            config.n_decks = decks as usize;
+        }
+        if let Some(shuffles) = self.shuffles {
+            config.n_shuffles = shuffles;
+        }
+        if let Some(min_bet) = self.min_bet {
+            config.minimum_bet = min_bet;
+        }
+        if self.max_bet.is_some() {
+            config.maximum_bet = self.max_bet;
+        }
+        if let Some(bankroll) = self.bankroll {
+            config.table_balance = bankroll;
+        }
+        // A side bet the file already offers keeps the paytable the file gives it
+        for side_bet in &self.side_bet {
+            if !config.side_bets.iter().any(|(s, _)| s == side_bet) {
+                config.offer_side_bet(*side_bet, side_bet.default_paytable());
+            }
+        }
+        if self.seed.is_some() {
+            config.seed = self.seed;
+        }
+        if let Some(ui) = self.ui {
+            config.ui = ui;
+        }
+        if let Some(profile) = &self.profile {
+            config.profile_path = profile.clone();
+        }
+        let style = &mut config.card_style;
+        style.color &= !self.plain && !self.no_color;
+        style.unicode &= !self.plain && !self.no_unicode;
+        style.ascii_art = !self.plain && (style.ascii_art || self.ascii_art);
+        // Escape codes and symbols would end up in a file or pipe when the output is not a terminal
+        config.card_style = config.card_style.for_terminal();
+
+        // A setting given as an argument is named by its flag, any other by its key in the config file
+        let flags = [
+            ("player.name", "name", self.name.is_some()),
+            ("player.balance", "balance", self.balance.is_some()),
+            ("shoe.decks", "decks", self.decks.is_some()),
+            ("shoe.shuffles", "shuffles", self.shuffles.is_some()),
+            ("limits.min_bet", "min_bet", self.min_bet.is_some()),
+            ("limits.max_bet", "max_bet", self.max_bet.is_some()),
+            ("limits.bankroll", "bankroll", self.bankroll.is_some()),
+        ];
+        let mut command = Cli::command();
+        let name = |key: &str| {
+            flags
+                .iter()
+                .find(|(k, _, given)| *k == key && *given)
+                .and_then(|(_, id, _)| command.get_arguments().find(|arg| arg.get_id() == id))
+                .and_then(|arg| arg.get_long())
+                .map_or_else(|| key.to_string(), |long| format!("--{long}"))
+        };
+        let validated = config.validate_naming(name);
+        validated.map_err(|e| command.error(ErrorKind::ArgumentConflict, e))?;
+        Ok(config)
+    }
+}
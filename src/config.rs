@@ -0,0 +1,389 @@
+use crate::card_style::CardStyle;
+use crate::preset::Preset;
+use crate::profile::DEFAULT_PROFILE_PATH;
+use crate::side_bet::{Paytable, SideBet};
+use crate::variant::{BlackjackPayout, GameVariant, HoleCardRule, HouseRules};
+use crate::{BlackjackGameError, CutCard, ShoeMode};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The front-end a game is played with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UiMode {
+    /// A scrolling stream of lines printed to the console
+    #[default]
+    Console,
+    /// A full-screen table drawn in the terminal
+    Tui,
+}
+
+/// The settings a game is started with
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    /// The players name, the player is asked for their name if it is None
+    pub name: Option<String>,
+    /// The balance a new player starts with, and that a player who can no longer cover the minimum bet is restaked to
+    pub starting_balance: f32,
+    /// The bankroll of the table, bets the table could not pay out are refused
+    pub table_balance: f32,
+    pub n_decks: usize,
+    pub n_shuffles: u32,
+    pub minimum_bet: u32,
+    /// The largest bet allowed on a single hand, or None if there is no maximum
+    pub maximum_bet: Option<u32>,
+    pub variant: GameVariant,
+    pub hole_card: HoleCardRule,
+    pub rules: HouseRules,
+    pub cut_card: CutCard,
+    /// The number of cards burned from the top of the shoe after every shuffle
+    pub burn_cards: usize,
+    pub shoe_mode: ShoeMode,
+    /// The side bets offered at the table and the paytables they are paid with
+    pub side_bets: Vec<(SideBet, Paytable)>,
+    /// Seeds the shuffling of the shoe so a game can be dealt again, the shoe is shuffled randomly if it is None
+    pub seed: Option<u64>,
+    pub ui: UiMode,
+    /// The file player profiles are loaded from and saved to
+    pub profile_path: String,
+    pub card_style: CardStyle,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            name: None,
+            starting_balance: 500.0,
+            table_balance: 500000000.0,
+            n_decks: 6,
+            n_shuffles: 7,
+            minimum_bet: 5,
+            maximum_bet: None,
+            variant: GameVariant::default(),
+            hole_card: HoleCardRule::default(),
+            rules: HouseRules::default(),
+            cut_card: CutCard::Fixed(0.8),
+            burn_cards: 0,
+            shoe_mode: ShoeMode::HandShuffled,
+            side_bets: vec![],
+            seed: None,
+            ui: UiMode::default(),
+            profile_path: DEFAULT_PROFILE_PATH.to_string(),
+            card_style: CardStyle::default(),
+        }
+    }
+}
+
+impl GameConfig {
+    /// Offers `side_bet` paid by `paytable`, replacing the paytable if the side bet is already offered
+    pub fn offer_side_bet(&mut self, side_bet: SideBet, paytable: Paytable) {
+        match self.side_bets.iter_mut().find(|(s, _)| *s == side_bet) {
+            Some(offered) => offered.1 = paytable,
+            None => self.side_bets.push((side_bet, paytable)),
+        }
+    }
+
+    /// Checks that a game can be played with the configuration, returning an error describing the first setting that is invalid
+    /// by its key in the config file
+    pub fn validate(&self) -> Result<(), BlackjackGameError> {
+        self.validate_naming(|key| key.to_string())
+    }
+
+    /// Checks that a game can be played with the configuration like `validate`, naming each setting in the error with
+    /// `name`, which is given the key of the setting in the config file such as `limits.max_bet`
+    pub fn validate_naming(&self, name: impl Fn(&str) -> String) -> Result<(), BlackjackGameError> {
+        let invalid = |message: String| Err(BlackjackGameError::new(message));
+        if self
+            .name
+            .as_ref()
+            .is_some_and(|name| name.trim().is_empty())
+        {
+            return invalid(format!("{} cannot be empty", name("player.name")));
+        }
+        if !(1..=8).contains(&self.n_decks) {
+            return invalid(format!(
+                "{} {} is not between 1 and 8 decks",
+                name("shoe.decks"),
+                self.n_decks
+            ));
+        }
+        if !(1..=100).contains(&self.n_shuffles) {
+            return invalid(format!(
+                "{} {} is not between 1 and 100 shuffles",
+                name("shoe.shuffles"),
+                self.n_shuffles
+            ));
+        }
+        if self.minimum_bet == 0 {
+            return invalid(format!("{} must be at least $1", name("limits.min_bet")));
+        }
+        if let Some(maximum_bet) = self.maximum_bet.filter(|max| *max < self.minimum_bet) {
+            return invalid(format!(
+                "{} ${maximum_bet} is below {} ${}",
+                name("limits.max_bet"),
+                name("limits.min_bet"),
+                self.minimum_bet
+            ));
+        }
+        if !self.starting_balance.is_finite() || self.starting_balance < self.minimum_bet as f32 {
+            return invalid(format!(
+                "{} ${} cannot cover {} ${}",
+                name("player.balance"),
+                self.starting_balance,
+                name("limits.min_bet"),
+                self.minimum_bet
+            ));
+        }
+        if !self.table_balance.is_finite() || self.table_balance < 1.5 * self.minimum_bet as f32 {
+            return invalid(format!(
+                "{} ${} cannot pay out {} ${}",
+                name("limits.bankroll"),
+                self.table_balance,
+                name("limits.min_bet"),
+                self.minimum_bet
+            ));
+        }
+        self.cut_card.validate()?;
+
+        let n_cards = self
+            .variant
+            .build_deck(self.n_decks)
+            .remaining_cards()
+            .len();
+        if self.burn_cards >= n_cards {
+            return invalid(format!(
+                "{} {} does not leave a card to deal from the {n_cards} cards of {} {}",
+                name("shoe.burn_cards"),
+                self.burn_cards,
+                name("shoe.decks"),
+                self.n_decks
+            ));
+        }
+        if let ShoeMode::Continuous { buffer } = self.shoe_mode {
+            if buffer >= n_cards {
+                return invalid(format!(
+                    "{} {buffer} does not fit in the {n_cards} cards of {} {}",
+                    name("shoe.csm_buffer"),
+                    name("shoe.decks"),
+                    self.n_decks
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where the cut card is placed, either a fixed penetration such as `0.75` or a range such as `[0.7, 0.8]`
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Penetration {
+    Fixed(f32),
+    Random([f32; 2]),
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct PlayerSettings {
+    name: Option<String>,
+    balance: Option<f32>,
+    profile: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RuleSettings {
+    variant: Option<GameVariant>,
+    hole_card: Option<HoleCardRule>,
+    dealer_hits_soft_17: Option<bool>,
+    blackjack_pays: Option<BlackjackPayout>,
+    late_surrender: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct LimitSettings {
+    min_bet: Option<u32>,
+    max_bet: Option<u32>,
+    bankroll: Option<f32>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ShoeSettings {
+    decks: Option<usize>,
+    shuffles: Option<u32>,
+    penetration: Option<Penetration>,
+    burn_cards: Option<usize>,
+    /// Deals from a continuous shuffling machine that keeps this many cards ready to deal
+    csm_buffer: Option<usize>,
+    seed: Option<u64>,
+}
+
+/// The side bets offered, each given by the payouts it pays instead of its usual paytable, which may be none
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct SideBetSettings {
+    perfect_pairs: Option<BTreeMap<String, f32>>,
+    twenty_one_plus_three: Option<BTreeMap<String, f32>>,
+    lucky_ladies: Option<BTreeMap<String, f32>>,
+    buster_blackjack: Option<BTreeMap<String, f32>>,
+}
+
+impl SideBetSettings {
+    /// Returns the side bets offered along with their paytables, or an error if a payout is not valid
+    fn paytables(&self) -> Result<Vec<(SideBet, Paytable)>, BlackjackGameError> {
+        [
+            (SideBet::PerfectPairs, &self.perfect_pairs),
+            (SideBet::TwentyOnePlusThree, &self.twenty_one_plus_three),
+            (SideBet::LuckyLadies, &self.lucky_ladies),
+            (SideBet::BusterBlackjack, &self.buster_blackjack),
+        ]
+        .into_iter()
+        .filter_map(|(side_bet, payouts)| payouts.as_ref().map(|p| (side_bet, p)))
+        .map(|(side_bet, payouts)| {
+            let payouts: Vec<(&str, f32)> = payouts.iter().map(|(o, m)| (o.as_str(), *m)).collect();
+            Ok((side_bet, side_bet.paytable_with(&payouts)?))
+        })
+        .collect()
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct UiSettings {
+    mode: Option<UiMode>,
+    color: Option<bool>,
+    unicode: Option<bool>,
+    ascii_art: Option<bool>,
+}
+
+/// A table definition read from a TOML file, every setting is optional and only the settings given are applied, e.g.
+///
+/// ```toml
+/// preset = "vegas-strip"
+///
+/// [rules]
+/// dealer_hits_soft_17 = true
+/// blackjack_pays = "6:5"
+///
+/// [limits]
+/// min_bet = 10
+/// max_bet = 500
+///
+/// [shoe]
+/// decks = 2
+/// penetration = [0.65, 0.75]
+///
+/// [side_bets]
+/// perfect_pairs = {}
+/// twenty_one_plus_three = { "straight" = 9, "flush" = 4 }
+/// ```
+///
+/// The sections are `[player]` (name, balance, profile), `[rules]` (variant, hole_card, dealer_hits_soft_17, blackjack_pays,
+/// late_surrender), `[limits]` (min_bet, max_bet, bankroll), `[shoe]` (decks, shuffles, penetration, burn_cards, csm_buffer,
+/// seed), `[side_bets]` (perfect_pairs, twenty_one_plus_three, lucky_ladies, buster_blackjack, each offering the side bet
+/// with any payouts overriding its usual paytable) and `[ui]` (mode, color, unicode, ascii_art)
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    preset: Option<Preset>,
+    player: PlayerSettings,
+    rules: RuleSettings,
+    limits: LimitSettings,
+    shoe: ShoeSettings,
+    side_bets: SideBetSettings,
+    ui: UiSettings,
+}
+
+impl ConfigFile {
+    /// Reads the table definition in the TOML file at `path`, returning an error naming the file and the setting that
+    /// could not be read
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ConfigFile, BlackjackGameError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            BlackjackGameError::new(format!("Could not read {}: {e}", path.display()))
+        })?;
+        Self::parse(&contents)
+            .map_err(|e| BlackjackGameError::new(format!("{}: {e}", path.display())))
+    }
+
+    /// Reads a table definition from the TOML `contents`
+    pub fn parse(contents: &str) -> Result<ConfigFile, BlackjackGameError> {
+        let file: ConfigFile =
+            toml::from_str(contents).map_err(|e| BlackjackGameError::new(e.to_string()))?;
+        // The payouts are checked here so that applying the file cannot fail
+        file.side_bets.paytables()?;
+        Ok(file)
+    }
+
+    /// Returns the preset the file builds its table on, if it names one
+    pub fn preset(&self) -> Option<Preset> {
+        self.preset
+    }
+
+    /// Overwrites the settings of `config` with every setting given in the file. The preset is not applied, so that it
+    /// can be applied first and overridden by the file
+    pub fn apply(&self, config: &mut GameConfig) {
+        fn set<T: Clone>(setting: &Option<T>, value: &mut T) {
+            if let Some(setting) = setting {
+                *value = setting.clone();
+            }
+        }
+
+        let player = &self.player;
+        if player.name.is_some() {
+            config.name = player.name.clone();
+        }
+        set(&player.balance, &mut config.starting_balance);
+        set(&player.profile, &mut config.profile_path);
+
+        let rules = &self.rules;
+        set(&rules.variant, &mut config.variant);
+        set(&rules.hole_card, &mut config.hole_card);
+        set(
+            &rules.dealer_hits_soft_17,
+            &mut config.rules.dealer_hits_soft_17,
+        );
+        set(&rules.blackjack_pays, &mut config.rules.blackjack_pays);
+        set(&rules.late_surrender, &mut config.rules.late_surrender);
+
+        let limits = &self.limits;
+        set(&limits.min_bet, &mut config.minimum_bet);
+        if limits.max_bet.is_some() {
+            config.maximum_bet = limits.max_bet;
+        }
+        set(&limits.bankroll, &mut config.table_balance);
+
+        let shoe = &self.shoe;
+        set(&shoe.decks, &mut config.n_decks);
+        set(&shoe.shuffles, &mut config.n_shuffles);
+        match shoe.penetration {
+            Some(Penetration::Fixed(f)) => config.cut_card = CutCard::Fixed(f),
+            Some(Penetration::Random([low, high])) => config.cut_card = CutCard::Random(low, high),
+            None => {}
+        }
+        set(&shoe.burn_cards, &mut config.burn_cards);
+        if let Some(buffer) = shoe.csm_buffer {
+            config.shoe_mode = ShoeMode::Continuous { buffer };
+        }
+        if shoe.seed.is_some() {
+            config.seed = shoe.seed;
+        }
+
+        let side_bets = self
+            .side_bets
+            .paytables()
+            .expect("side bet paytables are checked when the file is parsed");
+        for (side_bet, paytable) in side_bets {
+            config.offer_side_bet(side_bet, paytable);
+        }
+
+        let ui = &self.ui;
+        set(&ui.mode, &mut config.ui);
+        set(&ui.color, &mut config.card_style.color);
+        set(&ui.unicode, &mut config.card_style.unicode);
+        set(&ui.ascii_art, &mut config.card_style.ascii_art);
+    }
+}
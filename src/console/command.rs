@@ -0,0 +1,77 @@
+use std::fmt::Display;
+
+/// A command the player can type at any prompt of a console game, instead of a bet or one of their options
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameCommand {
+    Balance,
+    Stats,
+    Session,
+    Count,
+    Hint,
+    History,
+    Rules,
+    Save,
+    Quit,
+    Help,
+}
+
+/// Every command along with the description listed by `help`
+pub const COMMANDS: [(GameCommand, &str); 10] = [
+    (GameCommand::Balance, "your balance and the bets in play"),
+    (GameCommand::Stats, "your lifetime statistics"),
+    (GameCommand::Session, "the statistics of this session"),
+    (
+        GameCommand::Count,
+        "the Hi-Lo running and true count of the shoe",
+    ),
+    (GameCommand::Hint, "the basic strategy play for your hand"),
+    (GameCommand::History, "the last rounds played"),
+    (GameCommand::Rules, "the rules and limits of the table"),
+    (GameCommand::Save, "save your profile"),
+    (
+        GameCommand::Quit,
+        "leave the table, standing on any hands still in play",
+    ),
+    (GameCommand::Help, "list the commands"),
+];
+
+impl GameCommand {
+    /// Parses `input` as a command, ignoring case and surrounding whitespace. Returns None if `input` is not a command
+    pub fn parse(input: &str) -> Option<GameCommand> {
+        let input = input.trim();
+        COMMANDS
+            .iter()
+            .map(|(command, _)| *command)
+            .find(|command| command.name().eq_ignore_ascii_case(input))
+    }
+
+    /// Returns the name the command is typed as
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameCommand::Balance => "balance",
+            GameCommand::Stats => "stats",
+            GameCommand::Session => "session",
+            GameCommand::Count => "count",
+            GameCommand::Hint => "hint",
+            GameCommand::History => "history",
+            GameCommand::Rules => "rules",
+            GameCommand::Save => "save",
+            GameCommand::Quit => "quit",
+            GameCommand::Help => "help",
+        }
+    }
+
+    /// Prints every command along with its description to the console
+    pub fn display_help() {
+        println!("Commands, available at any prompt:");
+        for (command, description) in COMMANDS {
+            println!("\t{:<10}{}", command.name(), description);
+        }
+    }
+}
+
+impl Display for GameCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -1,45 +1,186 @@
+pub mod command;
 pub mod player;
 pub mod table;
+pub mod tui;
 
+use crate::action::{choose_option, Action};
+use crate::console::command::GameCommand;
 use crate::console::player::ConsolePlayer;
 use crate::console::table::ConsoleBlackjackTable;
+use crate::history::{HandHistory, HandHistoryWriter};
+use crate::profile::ProfileStore;
+use crate::session::SessionStats;
 use crate::BlackjackTable;
+use std::collections::HashMap;
 use std::str::FromStr;
 
+/// The number of rounds kept for the `history` command
+const RECENT_ROUNDS: usize = 5;
+
+/// Returns the option played for a player who quit in the middle of a round, declining insurance and standing wherever
+/// the rules let them and otherwise drawing, e.g. a Pontoon hand below 15
+fn quitting_option(options: &HashMap<i32, Action>) -> i32 {
+    [
+        Action::NoInsurance,
+        Action::Stand,
+        Action::Stick,
+        Action::Keep,
+        Action::Twist,
+        Action::Hit,
+    ]
+    .iter()
+    .find_map(|play| options.iter().find(|(_, o)| *o == play))
+    .map(|(i, _)| *i)
+    .expect("player should be able to stand or draw")
+}
+
 /// A struct for implementing the control flow logic/error checking of a blackjack game played via the console
 pub struct ConsoleBlackjackGame {
     table: ConsoleBlackjackTable,
     player: ConsolePlayer,
     minimum_bet: u32,
+    profiles: Option<ProfileStore>,
+    history: Option<HandHistoryWriter>,
+    recent_rounds: Vec<HandHistory>,
+    session: SessionStats,
+    /// The player quit in the middle of a round, so the round is played out and then the game ends
+    quitting: bool,
 }
 
 impl ConsoleBlackjackGame {
-    /// Returns a new BlackjackGameCLI
+    /// Returns a new BlackjackGameCLI, if `profiles` is given the players profile is saved to it at the end of every round,
+    /// and if `history` is given every round is written to the hand history logs
     pub fn new(
         minimum_bet: u32,
         player: ConsolePlayer,
         table: ConsoleBlackjackTable,
+        profiles: Option<ProfileStore>,
+        history: Option<HandHistoryWriter>,
     ) -> ConsoleBlackjackGame {
         ConsoleBlackjackGame {
+            session: SessionStats::new(player.balance),
             minimum_bet,
             player,
             table,
+            profiles,
+            history,
+            recent_rounds: vec![],
+            quitting: false,
         }
     }
 
-    /// Plays a game of blackjack
+    /// Writes the history of the round that just finished to the hand history logs, if the game has them, records it in
+    /// the session statistics and keeps it for the `history` command
+    fn write_hand_history(&mut self) {
+        let Some(history) = self.table.take_hand_history() else {
+            return;
+        };
+        if let Some(writer) = self.history.as_mut() {
+            if let Err(e) = writer.write(&history) {
+                println!("Error writing hand history, {e}");
+            }
+        }
+        self.session.record_round(&history, self.player.balance);
+        self.recent_rounds.push(history);
+        if self.recent_rounds.len() > RECENT_ROUNDS {
+            self.recent_rounds.remove(0);
+        }
+    }
+
+    /// Saves the players current balance and statistics to the profile store, if the game has one
+    fn save_profile(&mut self) {
+        if let Some(profiles) = self.profiles.as_mut() {
+            profiles.update(self.player.to_profile());
+            if let Err(e) = profiles.save() {
+                println!("Error saving profile, {e}");
+            }
+        }
+    }
+
+    /// Reads a line of input from the player, running every command they type until they enter something else.
+    /// `options` should be the players options while they are playing a hand.
+    /// Returns None if the player quit or there is no more input
+    fn read_input(
+        &mut self,
+        options: Option<&HashMap<i32, Action>>,
+    ) -> std::io::Result<Option<String>> {
+        loop {
+            let mut users_input = String::new();
+            if std::io::stdin().read_line(&mut users_input)? == 0 {
+                return Ok(None);
+            }
+            match GameCommand::parse(&users_input) {
+                Some(GameCommand::Quit) => return Ok(None),
+                Some(command) => self.run_command(command, options),
+                None => return Ok(Some(users_input.trim().to_string())),
+            }
+        }
+    }
+
+    /// Runs any command other than `quit`, which `read_input` handles
+    fn run_command(&mut self, command: GameCommand, options: Option<&HashMap<i32, Action>>) {
+        match command {
+            GameCommand::Balance => {
+                let side_bets: u32 = self.player.side_bets.iter().map(|(_, bet)| bet).sum();
+                let in_play = self.player.bets().iter().sum::<u32>() + side_bets;
+                println!("{:<20}${:.2}", "Balance:", self.player.balance);
+                println!("{:<20}${}", "Bets in play:", in_play);
+            }
+            GameCommand::Stats => self.player.stats.display(),
+            GameCommand::Session => self.session.display(self.table.card_style().unicode),
+            GameCommand::Count => {
+                let decks_remaining = self.table.deck().remaining_cards().len() as f32 / 52.0;
+                println!(
+                    "Running count {:+}, true count {:+.1}, {:.1} decks remaining",
+                    self.table.running_count(),
+                    self.table.true_count(),
+                    decks_remaining
+                );
+            }
+            GameCommand::Hint => match options {
+                None => println!("There is no hand in play to give a hint for"),
+                Some(options) => match self.table.suggested_option(&self.player, options) {
+                    Some(option) => println!("Basic strategy says: {option}"),
+                    None => println!("Hints are only given for Standard blackjack"),
+                },
+            },
+            GameCommand::History if self.recent_rounds.is_empty() => {
+                println!("No rounds have been played yet")
+            }
+            GameCommand::History => {
+                for history in &self.recent_rounds {
+                    println!("{history}");
+                }
+            }
+            GameCommand::Rules => self.table.display_rules(),
+            GameCommand::Save => match self.profiles.as_mut() {
+                None => println!("This game does not save profiles"),
+                Some(profiles) => {
+                    profiles.update(self.player.to_profile());
+                    match profiles.save() {
+                        Ok(()) => println!("Saved the profile of {}", self.player.name()),
+                        Err(e) => println!("Error saving profile, {e}"),
+                    }
+                }
+            },
+            GameCommand::Help => GameCommand::display_help(),
+            GameCommand::Quit => {}
+        }
+    }
+
+    /// Plays a game of blackjack. Commands can be typed at any prompt, and quitting in the middle of a round plays the
+    /// round out standing on the players remaining hands. The session statistics are reported when the game ends
     pub fn play(&mut self) -> std::io::Result<()> {
+        println!("Type help at any prompt for a list of commands");
         // The main game loop
         'main: loop {
             // get a valid bet from the player via console
             let bet = loop {
                 println!("Please enter a valid bet, minimum ${}", self.minimum_bet);
-                let mut players_entered_bet = String::new();
-                if let Err(e) = std::io::stdin().read_line(&mut players_entered_bet) {
-                    println!("Error parsing bet, {e}");
-                    continue;
-                }
-                match u32::from_str(players_entered_bet.trim()) {
+                let Some(players_entered_bet) = self.read_input(None)? else {
+                    break 'main;
+                };
+                match u32::from_str(&players_entered_bet) {
                     Err(e) => {
                         println!("{e}");
                         println!("Error parsing entered amount as an integer, please ensure entered amount is parseable as a non-negative integer");
@@ -58,30 +199,58 @@ impl ConsoleBlackjackGame {
                 println!("{e}");
                 continue;
             }
+            // Offer the player every side bet the table has, an empty bet skips the side bet
+            'side_bets: for side_bet in self.table.side_bets() {
+                loop {
+                    println!("Enter a bet on {side_bet}, or nothing to skip");
+                    let Some(users_input) = self.read_input(None)? else {
+                        self.quitting = true;
+                        break 'side_bets;
+                    };
+                    if users_input.is_empty() {
+                        break;
+                    }
+                    let side_bet_result = u32::from_str(&users_input)
+                        .map_err(|e| e.to_string())
+                        .and_then(|n| {
+                            self.table
+                                .place_side_bet(&mut self.player, side_bet, n)
+                                .map_err(|e| e.to_string())
+                        });
+                    match side_bet_result {
+                        Ok(()) => break,
+                        Err(e) => println!("{e}"),
+                    }
+                }
+            }
+
             // deal hand
             self.table.deal_hand(&mut self.player);
 
-            while !self.player.turn_is_over() {
-                let options = self.player.get_playing_options();
+            'hand: while !self.player.turn_is_over() {
+                let options = self.table.playing_options(&self.player);
+                if self.quitting {
+                    self.table
+                        .play_option(&mut self.player, &options, quitting_option(&options))
+                        .expect("quitting option should be one of the players options");
+                    continue;
+                }
                 self.table.display_playing_options(&options, &self.player);
                 'outer: loop {
                     let option = 'validation_loop: loop {
-                        let mut users_input = String::new();
-                        std::io::stdin().read_line(&mut users_input)?;
+                        let Some(users_input) = self.read_input(Some(&options))? else {
+                            println!("Standing on your remaining hands to finish the round");
+                            self.quitting = true;
+                            continue 'hand;
+                        };
 
-                        let choice = match i32::from_str(users_input.trim()) {
-                            Ok(n) => n,
-                            Err(e) => {
-                                println!("{e}");
+                        // An option can be chosen by its number, its key or its name
+                        match choose_option(&options, &users_input) {
+                            Some(choice) => break 'validation_loop choice,
+                            None => {
+                                println!("Please enter a valid option");
                                 continue;
                             }
-                        };
-
-                        if options.contains_key(&choice) {
-                            break 'validation_loop choice;
-                        } else {
-                            println!("Please enter a valid option");
-                            continue;
                         }
                     };
 
@@ -96,27 +265,28 @@ impl ConsoleBlackjackGame {
             }
 
             self.table.finish_hand(&mut self.player);
+            self.write_hand_history();
+            self.save_profile();
+            if self.quitting {
+                break 'main;
+            }
             println!("\n");
             println!("Play another round? (y/n): ");
-            let mut users_input = String::new();
             loop {
-                std::io::stdin().read_line(&mut users_input)?;
-                if users_input.trim().to_lowercase() == "y"
-                    || users_input.trim().to_lowercase() == "n"
-                {
-                    break;
+                let Some(users_input) = self.read_input(None)? else {
+                    break 'main;
+                };
+                match users_input.to_lowercase().as_str() {
+                    "y" => continue 'main,
+                    "n" => break 'main,
+                    _ => println!("please enter a valid choice"),
                 }
-                println!("please enter a valid choice");
-                users_input = String::new();
-            }
-
-            if users_input.trim() == "y" {
-                continue;
             }
-
-            break 'main;
         }
 
+        if self.session.rounds > 0 {
+            self.session.display(self.table.card_style().unicode);
+        }
         Ok(())
     }
 }
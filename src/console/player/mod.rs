@@ -1,4 +1,8 @@
 mod players_hand;
+use crate::action::Action;
+use crate::card_style::CardStyle;
+use crate::profile::{PlayerProfile, PlayerStats};
+use crate::side_bet::SideBet;
 use crate::{compute_optimal_hand, BlackjackGameError, Card, Player};
 use players_hand::ConsolePlayersBlackjackHand;
 use std::collections::HashMap;
@@ -10,6 +14,10 @@ pub struct ConsolePlayer {
     pub balance: f32,
     pub bj_hand: ConsolePlayersBlackjackHand,
     pub hand_idx: usize,
+    pub stats: PlayerStats,
+    /// The side bets placed for the current round
+    pub side_bets: Vec<(SideBet, u32)>,
+    round_start_balance: f32,
 }
 
 impl ConsolePlayer {
@@ -20,9 +28,33 @@ impl ConsolePlayer {
             balance,
             bj_hand: ConsolePlayersBlackjackHand::new(),
             hand_idx: 0usize,
+            stats: PlayerStats::default(),
+            side_bets: vec![],
+            round_start_balance: balance,
         }
     }
 
+    /// Creates a new player struct from a saved profile, restoring the players balance and lifetime statistics
+    pub fn from_profile(profile: PlayerProfile) -> ConsolePlayer {
+        let mut player = ConsolePlayer::new(profile.name, profile.balance);
+        player.stats = profile.stats;
+        player
+    }
+
+    /// Returns a profile capturing the players current balance and lifetime statistics, for saving to a `ProfileStore`
+    pub fn to_profile(&self) -> PlayerProfile {
+        PlayerProfile {
+            name: self.name.clone(),
+            balance: self.balance,
+            stats: self.stats.clone(),
+        }
+    }
+
+    /// A simple getter method that returns the players name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Gets the optimal hand of a player for valid bet the player has. If the player has no more valid bets, then
     /// the method returns None
     pub fn get_optimal_hands(&self) -> Option<HashMap<usize, u8>> {
@@ -41,6 +73,18 @@ impl ConsolePlayer {
     /// Only icreases players hand index by 1 in order to signal that this hand is finished.
     pub fn stand(&mut self) {
         self.hand_idx += 1;
+        self.skip_settled_hands();
+    }
+
+    /// Moves the players hand index past any hands that need no further decisions, i.e. hands that have already been
+    /// settled and no longer have a bet, such as a blackjack paid out before the player acts on their other hands,
+    /// and natural blackjacks waiting to be settled against the dealer
+    pub fn skip_settled_hands(&mut self) {
+        while self.hand_idx < self.bj_hand.bets.len()
+            && (self.bj_hand.bets[self.hand_idx] == 0 || self.bj_hand.is_natural(self.hand_idx))
+        {
+            self.hand_idx += 1;
+        }
     }
 
     /// Provides a boolean flag signaling whether or not the player is finished with their hand
@@ -56,37 +100,60 @@ impl ConsolePlayer {
                 message: "Insufficient funds to place that bet".to_string(),
             });
         }
+        if self.bj_hand.bets.is_empty() && self.side_bets.is_empty() {
+            self.round_start_balance = self.balance;
+        }
         self.balance -= bet;
         self.bj_hand.place_bet(bet as u32);
         Ok(())
     }
 
+    /// Takes `bet` and places it on the side bet `side_bet` for the current round
+    pub fn place_side_bet(
+        &mut self,
+        side_bet: SideBet,
+        bet: u32,
+    ) -> Result<(), BlackjackGameError> {
+        if bet as f32 > self.balance {
+            return Err(BlackjackGameError {
+                message: "Insufficient funds to place that side bet".to_string(),
+            });
+        }
+        if self.bj_hand.bets.is_empty() && self.side_bets.is_empty() {
+            self.round_start_balance = self.balance;
+        }
+        self.balance -= bet as f32;
+        self.side_bets.push((side_bet, bet));
+        Ok(())
+    }
+
     /// Returns the value of the current bet and resets its value to 0 for post processing
     /// Increases the players hand_idx by 1, to signal this hand is finished.
     pub fn lose_bet(&mut self) -> u32 {
         let bet = self.bj_hand.lose_bet(self.hand_idx);
         self.hand_idx += 1;
+        self.skip_settled_hands();
         bet
     }
 
     /// Queries the players hand struct to see what the valid options are for the player to takes
     /// function will panic if the players current hand has busted or the player has not placed any bets
-    pub fn get_playing_options(&self) -> HashMap<i32, String> {
+    pub fn get_playing_options(&self) -> HashMap<i32, Action> {
         assert!(!self.bj_hand.busted(self.hand_idx), "hand should be over");
         assert!(
             !self.bj_hand.bets.is_empty(),
             "player should have placed a bet"
         );
         let mut playing_options = HashMap::new();
-        playing_options.insert(1, "stand".to_string());
-        playing_options.insert(2, "hit".to_string());
+        playing_options.insert(1, Action::Stand);
+        playing_options.insert(2, Action::Hit);
         let mut playing_option = 3;
 
         if self.bj_hand.can_split(self.hand_idx)
             && self.balance >= (self.bj_hand.bets[self.hand_idx] as f32)
             && self.bj_hand.hand.len() < 4
         {
-            playing_options.insert(playing_option, "split".to_string());
+            playing_options.insert(playing_option, Action::Split);
             playing_option += 1;
         }
 
@@ -94,7 +161,7 @@ impl ConsolePlayer {
             && self.balance >= (self.bj_hand.bets[self.hand_idx] as f32)
             && self.bj_hand.hand.len() == 1
         {
-            playing_options.insert(playing_option, "double down".to_string());
+            playing_options.insert(playing_option, Action::DoubleDown);
         }
 
         playing_options
@@ -111,10 +178,33 @@ impl ConsolePlayer {
         self.balance -= cur_bet as f32;
     }
 
+    /// Method that allows the player to raise the bet of their current hand by `amount` to buy another card
+    pub fn buy(&mut self, amount: u32) {
+        self.bj_hand.buy(self.hand_idx, amount);
+        self.balance -= amount as f32;
+    }
+
+    /// Method that allows the player to double down on a bet for free, i.e. the house funds the doubled amount
+    pub fn free_double_down(&mut self) {
+        self.bj_hand.free_double_down(self.hand_idx);
+    }
+
+    /// Method that allows the player to split their current hand for free, i.e. the house funds the bet of the new hand.
+    /// Assumes all the conditions necessary for a valid split have been met
+    pub fn free_split(&mut self, card1: Rc<Card>, card2: Rc<Card>) {
+        self.bj_hand.free_split(self.hand_idx);
+        self.deal_split_cards(card1, card2);
+    }
+
     /// Method that allwos the player to split their current hand, assumes all the conditions necessary for a valid split have been met
     pub fn split(&mut self, card1: Rc<Card>, card2: Rc<Card>) {
         let cur_bet = self.bj_hand.split(self.hand_idx);
         self.balance -= cur_bet as f32;
+        self.deal_split_cards(card1, card2);
+    }
+
+    /// Deals the new cards `card1` and `card2` to the current hand and the hand just split from it
+    fn deal_split_cards(&mut self, card1: Rc<Card>, card2: Rc<Card>) {
         // Deal a the new cards to each new hand respectively, and compute their hand values
         self.bj_hand.receive_card(card1, self.hand_idx);
         self.bj_hand.compute_hand_value(self.hand_idx);
@@ -142,9 +232,9 @@ impl ConsolePlayer {
         &self.bj_hand.bets
     }
 
-    /// A wrapper method for self.bj_hand.display_hand(),  displays the players hand in a nice way
-    pub fn display_hand(&self) {
-        self.bj_hand.display_hand();
+    /// A wrapper method for self.bj_hand.display_hand(),  displays the players hand in a nice way with the cards drawn in `style`
+    pub fn display_hand(&self, style: &CardStyle) {
+        self.bj_hand.display_hand(style);
     }
 
     /// Displays the players balance to the console
@@ -152,9 +242,16 @@ impl ConsolePlayer {
         println!("{:<10}${}", "Balance:", self.balance)
     }
 
+    /// Records the net result of the round that is finishing in the players statistics
+    pub fn record_round(&mut self) {
+        self.stats
+            .record_round(self.balance - self.round_start_balance);
+    }
+
     /// Resets all of the necessary fields so the player can play another hand of blackjack
     pub fn reset(&mut self) {
         self.hand_idx = 0;
+        self.side_bets.clear();
         self.bj_hand.reset();
     }
 }
@@ -1,3 +1,4 @@
+use crate::card_style::{pad, visible_width, CardStyle};
 use crate::Card;
 use std::rc::Rc;
 
@@ -5,7 +6,14 @@ pub struct ConsolePlayersBlackjackHand {
     pub hand: Vec<Vec<Rc<Card>>>,
     pub hand_values: Vec<Vec<u8>>,
     pub bets: Vec<u32>,
-    hand_str: Vec<String>,
+    pub doubled: Vec<bool>,
+    pub from_split: Vec<bool>,
+    /// The part of each bet funded by the house rather than the player, i.e. free doubles and splits
+    pub free_bets: Vec<u32>,
+    /// The bet each hand started with, before any cards were bought or the bet was doubled
+    pub original_bets: Vec<u32>,
+    /// Whether each hand has twisted in Pontoon, after which no more cards can be bought for it
+    pub twisted: Vec<bool>,
     hand_values_str: Vec<String>,
     bets_str: Vec<String>,
 }
@@ -16,7 +24,11 @@ impl ConsolePlayersBlackjackHand {
         let hand = vec![vec![]];
         let hand_values = vec![vec![]];
         let bets = vec![];
-        let hand_str = vec![String::new()];
+        let doubled = vec![];
+        let from_split = vec![];
+        let free_bets = vec![];
+        let original_bets = vec![];
+        let twisted = vec![];
         let hand_values_str = vec![String::new()];
         let bets_str = vec![];
 
@@ -24,25 +36,64 @@ impl ConsolePlayersBlackjackHand {
             hand,
             hand_values,
             bets,
-            hand_str,
+            doubled,
+            from_split,
+            free_bets,
+            original_bets,
+            twisted,
             hand_values_str,
             bets_str,
         }
     }
 
     /// Takes in `bet` and updates the state of the bet associated with a particular hand.
+    /// Placing more than one bet gives the player an additional empty hand for each extra bet.
     pub fn place_bet(&mut self, bet: u32) {
         self.bets.push(bet);
+        self.doubled.push(false);
+        self.from_split.push(false);
+        self.free_bets.push(0);
+        self.original_bets.push(bet);
+        self.twisted.push(false);
         self.bets_str.push(bet.to_string());
+        if self.bets.len() > self.hand.len() {
+            self.hand.push(vec![]);
+            self.hand_values.push(vec![]);
+            self.hand_values_str.push(String::new());
+        }
     }
 
     /// Sets the bet of the current hand to 0 and returns the value of the current bet.
     pub fn lose_bet(&mut self, hand_idx: usize) -> u32 {
         let res = self.bets[hand_idx];
         self.bets[hand_idx] = 0;
+        self.free_bets[hand_idx] = 0;
         res
     }
 
+    /// Returns the part of the bet of the hand at index `hand_idx` that was funded by the player
+    pub fn stake(&self, hand_idx: usize) -> u32 {
+        self.bets[hand_idx] - self.free_bets[hand_idx]
+    }
+
+    /// Updates the string representing the bet of the hand at index `hand_idx`, showing any part of it that is free
+    fn update_bet_str(&mut self, hand_idx: usize) {
+        self.bets_str[hand_idx] = match self.free_bets[hand_idx] {
+            0 => self.bets[hand_idx].to_string(),
+            free => format!("{} ({} free)", self.bets[hand_idx], free),
+        };
+    }
+
+    /// Returns the string representing the value of the hand at index `hand_idx`, e.g. `7/17` for a soft hand
+    pub fn value_str(&self, hand_idx: usize) -> &str {
+        &self.hand_values_str[hand_idx]
+    }
+
+    /// Returns the string representing the bet of the hand at index `hand_idx`, showing any part of it that is free
+    pub fn bet_str(&self, hand_idx: usize) -> &str {
+        &self.bets_str[hand_idx]
+    }
+
     /// Simple function to check whether or not the current hand i.e. the hand at index `hand_idx` can split.
     /// The function will panice if `hand_idx` is not a valid index or the hand vector is empty.
     pub fn can_split(&self, hand_idx: usize) -> bool {
@@ -70,16 +121,14 @@ impl ConsolePlayersBlackjackHand {
         }
     }
 
-    /// Receive a new card, add it to the players hand and update the string representing
-    /// the current hand as well
+    /// Checks whether the hand at index `hand_idx` can be surrendered, i.e. it is the players first two cards and they have not split
+    pub fn can_surrender(&self, hand_idx: usize) -> bool {
+        hand_idx == 0 && self.hand.len() == 1 && self.hand[0].len() == 2
+    }
+
+    /// Receive a new card and add it to the players hand at index `hand_idx`
     pub fn receive_card(&mut self, card: Rc<Card>, hand_idx: usize) {
-        self.hand[hand_idx].push(Rc::clone(&card));
-        // Now update string representing hand
-        if self.hand_str[hand_idx].is_empty() {
-            self.hand_str[hand_idx].push_str(card.to_string().as_str());
-        } else {
-            self.hand_str[hand_idx].push_str(format!(" {}", card.to_string()).as_str());
-        }
+        self.hand[hand_idx].push(card);
     }
 
     /// Implement the logic for doubling down on a bet, updates the bets and the bets_str for display purposes.
@@ -87,16 +136,39 @@ impl ConsolePlayersBlackjackHand {
     pub fn double_down(&mut self, hand_idx: usize) -> u32 {
         let cur_bet = self.bets[hand_idx];
         self.bets[hand_idx] += cur_bet;
-        self.bets_str[hand_idx] = self.bets[hand_idx].to_string();
+        self.doubled[hand_idx] = true;
+        self.update_bet_str(hand_idx);
         cur_bet
     }
 
+    /// Raises the bet of the hand at index `hand_idx` by `amount` without ending the hand, i.e. buying a card in Pontoon
+    pub fn buy(&mut self, hand_idx: usize, amount: u32) {
+        self.bets[hand_idx] += amount;
+        self.update_bet_str(hand_idx);
+    }
+
+    /// Doubles down on the hand at index `hand_idx` with the house funding the doubled amount, i.e. a free double.
+    /// Returns the amount the house added to the bet.
+    pub fn free_double_down(&mut self, hand_idx: usize) -> u32 {
+        let free_bet = self.double_down(hand_idx);
+        self.free_bets[hand_idx] += free_bet;
+        self.update_bet_str(hand_idx);
+        free_bet
+    }
+
     /// Implements the logic for splitting a valid hand. Returns the value of the current bet to update the players balance.
     pub fn split(&mut self, hand_idx: usize) -> u32 {
         // Get current bet and add another to the vector that keeps track of the number of bets, also push another bet to the vector of strings
         // representing the current total number of bets the player has at the table
         let cur_bet = self.bets[hand_idx];
         self.bets.insert(hand_idx + 1, cur_bet);
+        self.doubled.insert(hand_idx + 1, false);
+        self.from_split[hand_idx] = true;
+        self.from_split.insert(hand_idx + 1, true);
+        self.free_bets.insert(hand_idx + 1, 0);
+        self.original_bets
+            .insert(hand_idx + 1, self.original_bets[hand_idx]);
+        self.twisted.insert(hand_idx + 1, false);
         self.bets_str.insert(hand_idx + 1, cur_bet.to_string());
 
         // Get the card that will be the geneis of the new hand, reset the hand value for the current hand and
@@ -110,14 +182,18 @@ impl ConsolePlayersBlackjackHand {
         self.hand_values_str[hand_idx].clear();
         self.hand_values_str.insert(hand_idx + 1, String::new());
 
-        // Now update the hand_str and hand_values_str
-        self.hand_str[hand_idx] = self.hand[hand_idx][0].to_string();
-        self.hand_str
-            .insert(hand_idx + 1, self.hand[hand_idx + 1][0].to_string());
-
         cur_bet
     }
 
+    /// Splits the hand at index `hand_idx` with the house funding the bet of the new hand, i.e. a free split.
+    /// Returns the amount the house bet on the new hand.
+    pub fn free_split(&mut self, hand_idx: usize) -> u32 {
+        let free_bet = self.split(hand_idx);
+        self.free_bets[hand_idx + 1] = free_bet;
+        self.update_bet_str(hand_idx + 1);
+        free_bet
+    }
+
     /// Compute the value of the players current hand and update the formatted string
     /// representing the value of the current hand as well
     pub fn compute_hand_value(&mut self, hand_idx: usize) {
@@ -170,10 +246,31 @@ impl ConsolePlayersBlackjackHand {
     /// checking that `self.hand_idx` is equal to zero i.e. it is the first hand dealt to the player, not
     /// a hand dealt after splitting
     pub fn is_blackjack(&self, hand_idx: usize) -> bool {
-        hand_idx == 0
-            && self.hand[0].len() == 2
-            && ((self.hand[0][0].rank == "A" && self.hand[0][1].val == 10)
-                || (self.hand[0][0].val == 10 && self.hand[0][1].rank == "A"))
+        hand_idx == 0 && self.is_ace_ten(0)
+    }
+
+    /// Checks if the hand at index `hand_idx` is made of exactly an ace and a ten valued card, without checking
+    /// whether the hand was dealt or is the result of a split
+    pub fn is_ace_ten(&self, hand_idx: usize) -> bool {
+        self.hand[hand_idx].len() == 2
+            && ((self.hand[hand_idx][0].rank == "A" && self.hand[hand_idx][1].val == 10)
+                || (self.hand[hand_idx][0].val == 10 && self.hand[hand_idx][1].rank == "A"))
+    }
+
+    /// Checks if the hand at index `hand_idx` is a natural blackjack, i.e. an ace and a ten valued card in a hand that
+    /// was dealt to the player rather than made by splitting
+    pub fn is_natural(&self, hand_idx: usize) -> bool {
+        !self.from_split[hand_idx] && self.is_ace_ten(hand_idx)
+    }
+
+    /// Swaps the second cards of the first two hands, as allowed in Blackjack Switch, and recomputes both hands values
+    pub fn switch_second_cards(&mut self) {
+        let card = Rc::clone(&self.hand[1][1]);
+        self.hand[1][1] = std::mem::replace(&mut self.hand[0][1], card);
+        for hand_idx in 0..2 {
+            self.hand_values[hand_idx].clear();
+            self.compute_hand_value(hand_idx);
+        }
     }
 
     /// Checks whether the current hand has busted or not
@@ -185,32 +282,44 @@ impl ConsolePlayersBlackjackHand {
         }
     }
 
-    /// Displays the players current blackjack hand in the console printed in a nice looking format
-    pub fn display_hand(&self) {
-        let mut formatted_hand_values_str = vec![];
-        let mut formatted_bet_str = vec![];
-        for (i, h) in self.hand_str.iter().enumerate() {
-            let width = h.len();
-            let bets_width = width - 1;
-            formatted_hand_values_str.push(format!("{:<width$}", self.hand_values_str[i]));
-            formatted_bet_str.push(format!("${:<bets_width$}", self.bets_str[i]));
-        }
-
-        let (formatted_hand_str, formatted_hand_values_str, formatted_bet_str) = (
-            self.hand_str.join(" | "),
-            formatted_hand_values_str.join(" | "),
-            formatted_bet_str.join(" | "),
-        );
+    /// Displays the players current blackjack hand in the console printed in a nice looking format, with the cards
+    /// drawn in `style`
+    pub fn display_hand(&self, style: &CardStyle) {
+        // Each hand is drawn in a column of its cards, value and bet, as wide as its widest line
+        let columns: Vec<Vec<String>> = self
+            .hand
+            .iter()
+            .enumerate()
+            .map(|(i, h)| {
+                let cards: Vec<Option<&Card>> = h.iter().map(|c| Some(c.as_ref())).collect();
+                let mut column = style.cards(&cards);
+                column.push(self.hand_values_str[i].clone());
+                column.push(format!("${}", self.bets_str[i]));
+                column
+            })
+            .collect();
+        let widths: Vec<usize> = columns
+            .iter()
+            .map(|c| c.iter().map(|line| visible_width(line)).max().unwrap_or(0))
+            .collect();
 
-        let bet_tag = if self.bets.len() > 1 {
-            "Bets:".to_string()
-        } else {
-            "Bet:".to_string()
-        };
-
-        println!("{:<10}{}", "You:", formatted_hand_str);
-        println!("{:<10}{}", "Value:", formatted_hand_values_str);
-        println!("{:<10}{}", bet_tag, formatted_bet_str);
+        let bet_tag = if self.bets.len() > 1 { "Bets:" } else { "Bet:" };
+        let n_lines = columns[0].len();
+        for line in 0..n_lines {
+            let tag = match line {
+                0 => "You:",
+                l if l == n_lines - 2 => "Value:",
+                l if l == n_lines - 1 => bet_tag,
+                _ => "",
+            };
+            let formatted_line = columns
+                .iter()
+                .zip(&widths)
+                .map(|(column, width)| pad(&column[line], *width))
+                .collect::<Vec<String>>()
+                .join(" | ");
+            println!("{:<10}{}", tag, formatted_line);
+        }
     }
 
     /// Resets the hand to a new empty hand
@@ -218,7 +327,11 @@ impl ConsolePlayersBlackjackHand {
         self.hand = vec![vec![]];
         self.hand_values = vec![vec![]];
         self.bets.clear();
-        self.hand_str = vec![String::from("")];
+        self.doubled.clear();
+        self.from_split.clear();
+        self.free_bets.clear();
+        self.original_bets.clear();
+        self.twisted.clear();
         self.hand_values_str = vec![String::from("")];
         self.bets_str.clear();
     }
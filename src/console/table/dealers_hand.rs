@@ -1,3 +1,4 @@
+use crate::card_style::CardStyle;
 use crate::{compute_optimal_hand, Card, Deck};
 use std::rc::Rc;
 
@@ -6,7 +7,6 @@ use std::rc::Rc;
 pub struct ConsoleDealersBlackjackHand {
     hand: Vec<Rc<Card>>,
     hand_value: Vec<u8>,
-    hand_str: String,
     hand_value_str: String,
 }
 
@@ -15,13 +15,11 @@ impl ConsoleDealersBlackjackHand {
     pub fn new() -> Self {
         let hand = vec![];
         let hand_value = vec![];
-        let hand_str = String::new();
         let hand_value_str = String::new();
 
         ConsoleDealersBlackjackHand {
             hand,
             hand_value,
-            hand_str,
             hand_value_str,
         }
     }
@@ -31,19 +29,55 @@ impl ConsoleDealersBlackjackHand {
         println!("{:<10}{}", "Value", self.hand_value_str);
     }
 
+    /// Prints `cards` drawn in `style` after the dealers tag, a card that is None is drawn face down
+    fn display_cards(cards: &[Option<&Card>], style: &CardStyle) {
+        for (i, line) in style.cards(cards).iter().enumerate() {
+            let tag = if i == 0 { "Dealer:" } else { "" };
+            println!("{:<10}{}", tag, line);
+        }
+    }
+
     /// Display dealers hand without revealing the hole card i.e at the begginning of a hand
-    pub fn display_hand_without_hole(&self) {
-        println!(
-            "{:<10}{} {}",
-            "Dealer:",
-            Card::display_facedown(),
-            self.hand[1]
-        );
+    pub fn display_hand_without_hole(&self, style: &CardStyle) {
+        // Without a hole card, i.e. dealing with no hole card, only the up card has been dealt
+        if self.hand.len() == 1 {
+            Self::display_cards(&[Some(&self.hand[0])], style);
+            return;
+        }
+        Self::display_cards(&[None, Some(&self.hand[1])], style);
+    }
+
+    /// Display dealers hand without revealing any of the dealers cards, i.e. in Pontoon where both cards are dealt face down
+    pub fn display_hand_hidden(&self, style: &CardStyle) {
+        Self::display_cards(&vec![None; self.hand.len()], style);
+    }
+
+    /// Returns the cards in the dealers hand, in the order they were received
+    pub fn cards(&self) -> &[Rc<Card>] {
+        &self.hand
     }
 
-    /// Print the value of dealers hand to the console, formatted in a nice way
-    pub fn display_hand(&self) {
-        println!("{:<10}{}", "Dealer:", self.hand_str);
+    /// Returns the dealers up card i.e. the card that is visible while the hole card is hidden
+    pub fn up_card(&self) -> &Card {
+        if self.hand.len() == 1 {
+            &self.hand[0]
+        } else {
+            &self.hand[1]
+        }
+    }
+
+    /// Receives the dealers second card `card` when it is drawn after the player has acted, i.e. when dealing with no hole card.
+    /// The card is placed before the up card so that it takes the place of the hole card, then the hand value is computed
+    pub fn receive_hole_card(&mut self, card: Rc<Card>) {
+        self.hand.insert(0, card);
+        self.hand_value.clear();
+        self.compute_hand_value();
+    }
+
+    /// Print the dealers hand to the console with every card face up, drawn in `style`
+    pub fn display_hand(&self, style: &CardStyle) {
+        let cards: Vec<Option<&Card>> = self.hand.iter().map(|c| Some(c.as_ref())).collect();
+        Self::display_cards(&cards, style);
     }
 
     /// Checks whether the dealers hand has busted or not
@@ -89,7 +123,7 @@ impl ConsoleDealersBlackjackHand {
             if self.hand_value[0] > 21 || self.hand_value[1] > 21 {
                 format!("{}", u8::min(self.hand_value[0], self.hand_value[1]))
             } else if self.hand_value[0] == 21 || self.hand_value[1] == 21 {
-                format!("21")
+                String::from("21")
             } else {
                 format!("{}/{}", self.hand_value[0], self.hand_value[1])
             }
@@ -99,37 +133,14 @@ impl ConsoleDealersBlackjackHand {
     }
 
     /// Receive a new card, `card` which will be pushed to dealers hand
-    /// function will also update the value of `self.hand_str`, the string representing the cards to be printed to the console
     pub fn receive_card(&mut self, card: Rc<Card>) {
-        self.hand.push(Rc::clone(&card));
-        if self.hand_str.is_empty() {
-            self.hand_str.push_str(card.to_string().as_str());
-        } else {
-            self.hand_str
-                .push_str(format!(" {}", card.to_string()).as_str());
-        }
+        self.hand.push(card);
     }
 
-    /// Method for computing the optimal, valid final hand according to the rules of blackjack
-    pub fn compute_optimal_final_hand(&mut self, deck: &mut Deck) -> u8 {
-        if self.hand_value.len() == 1 {
-            while self.hand_value[0] < 17 && self.hand_value.len() < 2 {
-                self.receive_card(deck.get_next_card().unwrap());
-                self.compute_hand_value();
-            }
-        }
-
-        // Check to see if we have a hand multiple hand values
-        while self.hand_value.len() == 2 && self.hand_value[0] < 17 && self.hand_value[1] < 17 {
-            self.receive_card(deck.get_next_card().unwrap());
-            self.compute_hand_value();
-        }
-
-        // Ensure the dealer has a valid hand no less than 17
-        while self.hand_value.len() == 2
-            && ((self.hand_value[0] < 17 && self.hand_value[1] > 21)
-                || (self.hand_value[0] > 21 && self.hand_value[1] < 17))
-        {
+    /// Method for computing the optimal, valid final hand according to the rules of blackjack.
+    /// The dealer stands on a soft 17 unless `hits_soft_17` is true
+    pub fn compute_optimal_final_hand(&mut self, deck: &mut Deck, hits_soft_17: bool) -> u8 {
+        while self.must_hit(hits_soft_17) {
             self.receive_card(deck.get_next_card().unwrap());
             self.compute_hand_value();
         }
@@ -138,11 +149,22 @@ impl ConsoleDealersBlackjackHand {
         compute_optimal_hand(&self.hand_value)
     }
 
+    /// Checks whether the dealer has to draw another card, i.e. their best hand value is below 17, or is a soft 17
+    /// when `hits_soft_17` is true
+    fn must_hit(&self, hits_soft_17: bool) -> bool {
+        match self.hand_value[..] {
+            [hard] => hard < 17,
+            // The soft value counts an ace as 11, so it is only the best value while it has not busted
+            [_, soft] if soft <= 21 => soft < 17 || (hits_soft_17 && soft == 17),
+            [hard, _] => hard < 17,
+            _ => false,
+        }
+    }
+
     /// Resets the dealers hand to play another round
     pub fn reset(&mut self) {
         self.hand.clear();
         self.hand_value.clear();
-        self.hand_str.clear();
         self.hand_value_str.clear();
     }
 }
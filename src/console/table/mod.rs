@@ -1,9 +1,16 @@
 mod dealers_hand;
 
+use crate::action::Action;
+use crate::card_style::CardStyle;
 use crate::console::player::ConsolePlayer;
-use crate::{BlackjackGameError, BlackjackTable, Deck, Player};
+use crate::history::{HandHistory, HandResult, RecordedAction, SideBetSettlement};
+use crate::side_bet::{Paytable, SideBet};
+use crate::strategy::{basic_strategy_option, hi_lo_value};
+use crate::variant::{GameVariant, HandRank, HoleCardRule, HouseRules, VariantPayout};
+use crate::{compute_optimal_hand, BlackjackGameError, BlackjackTable, Card, Deck};
 use dealers_hand::ConsoleDealersBlackjackHand;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 /// / A struct to implement the logic for a game of blackjack played over the console. Contains all the appropraite methods that imlement the
 /// / typical valid rules of a blackjack game. Intended to interact with a player struct.
@@ -12,12 +19,751 @@ pub struct ConsoleBlackjackTable {
     balance: f32,
     dealers_hand: ConsoleDealersBlackjackHand,
     n_shuffles: u32,
+    round: u32,
+    history: HandHistory,
+    completed_history: Option<HandHistory>,
+    variant: GameVariant,
+    hole_card: HoleCardRule,
+    rules: HouseRules,
+    switch_decided: bool,
+    /// The dealer shows an ace and is waiting for the players insurance decision before peeking for blackjack
+    insurance_open: bool,
+    side_bets: Vec<(SideBet, Paytable)>,
+    quiet: bool,
+    messages: Vec<String>,
+    card_style: CardStyle,
+    minimum_bet: u32,
+    maximum_bet: Option<u32>,
+}
+
+/// Numbers the options in `options` from 1 in the order they are given
+fn number_options(options: &[Action]) -> HashMap<i32, Action> {
+    options
+        .iter()
+        .enumerate()
+        .map(|(i, o)| (i as i32 + 1, *o))
+        .collect()
 }
 
 impl ConsoleBlackjackTable {
-    /// Takes a HashMap<i32, String> of numbered options and prints the options formatted nicely.
-    pub fn display_playing_options(&self, options: &HashMap<i32, String>, player: &ConsolePlayer) {
-        let display_tag = if player.bj_hand.hand.len() >= 2 {
+    /// Creates a new table that deals from `deck` rather than a newly built deck, i.e. a stacked deck,
+    /// and plays by the rules of `variant`
+    pub fn with_deck(
+        starting_balance: f32,
+        deck: Deck,
+        n_shuffles: u32,
+        variant: GameVariant,
+    ) -> Self {
+        ConsoleBlackjackTable {
+            deck,
+            balance: starting_balance,
+            dealers_hand: ConsoleDealersBlackjackHand::new(),
+            n_shuffles,
+            round: 0,
+            history: HandHistory::default(),
+            completed_history: None,
+            variant,
+            hole_card: HoleCardRule::default(),
+            rules: HouseRules::default(),
+            switch_decided: false,
+            insurance_open: false,
+            side_bets: vec![],
+            quiet: false,
+            messages: vec![],
+            card_style: CardStyle::default(),
+            minimum_bet: 0,
+            maximum_bet: None,
+        }
+    }
+
+    /// Creates a new table that plays by the rules of `variant`, with a shoe of `n_decks` decks built for the variant
+    pub fn with_variant(
+        starting_balance: f32,
+        n_decks: usize,
+        n_shuffles: u32,
+        variant: GameVariant,
+    ) -> Self {
+        Self::with_deck(
+            starting_balance,
+            variant.build_deck(n_decks),
+            n_shuffles,
+            variant,
+        )
+    }
+
+    /// Returns the variant of blackjack played at the table
+    pub fn variant(&self) -> GameVariant {
+        self.variant
+    }
+
+    /// Sets how the dealer receives their second card, takes effect from the next round dealt
+    pub fn set_hole_card_rule(&mut self, hole_card: HoleCardRule) {
+        self.hole_card = hole_card;
+    }
+
+    /// Returns how the dealer receives their second card
+    pub fn hole_card_rule(&self) -> HoleCardRule {
+        self.hole_card
+    }
+
+    /// Sets the house rules the table deals with, takes effect from the next round dealt
+    pub fn set_house_rules(&mut self, rules: HouseRules) {
+        self.rules = rules;
+    }
+
+    /// Returns the house rules the table deals with
+    pub fn house_rules(&self) -> HouseRules {
+        self.rules
+    }
+
+    /// Stops the table printing the game to the console if `quiet` is true, the messages it would have printed are kept
+    /// for `take_messages` instead. Used by front-ends that draw the table themselves
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
+    /// Sets the smallest and largest bet allowed on a single hand, a `maximum` of None allows any bet the table can cover
+    pub fn set_bet_limits(&mut self, minimum: u32, maximum: Option<u32>) {
+        self.minimum_bet = minimum;
+        self.maximum_bet = maximum;
+    }
+
+    /// Returns the smallest and largest bet allowed on a single hand
+    pub fn bet_limits(&self) -> (u32, Option<u32>) {
+        (self.minimum_bet, self.maximum_bet)
+    }
+
+    /// Sets how the table draws the cards on the console
+    pub fn set_card_style(&mut self, card_style: CardStyle) {
+        self.card_style = card_style;
+    }
+
+    /// Returns how the table draws the cards on the console
+    pub fn card_style(&self) -> CardStyle {
+        self.card_style
+    }
+
+    /// Returns the messages kept while the table is quiet, oldest first, and clears them
+    pub fn take_messages(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.messages)
+    }
+
+    /// Prints `message` to the console, or keeps it for `take_messages` if the table is quiet
+    fn message(&mut self, message: String) {
+        if self.quiet {
+            self.messages.push(message);
+        } else {
+            println!("{message}");
+        }
+    }
+
+    /// Offers the side bet `side_bet` at the table, paid according to `paytable`. Offering a side bet that is already offered
+    /// replaces its paytable
+    pub fn offer_side_bet(&mut self, side_bet: SideBet, paytable: Paytable) {
+        match self.side_bets.iter_mut().find(|(s, _)| *s == side_bet) {
+            Some(offered) => offered.1 = paytable,
+            None => self.side_bets.push((side_bet, paytable)),
+        }
+    }
+
+    /// Returns the side bets offered at the table, in the order they were offered
+    pub fn side_bets(&self) -> Vec<SideBet> {
+        self.side_bets.iter().map(|(s, _)| *s).collect()
+    }
+
+    /// Takes a player `player` and places `bet` on the side bet `side_bet`, if the table offers it and can cover its payout
+    pub fn place_side_bet(
+        &self,
+        player: &mut ConsolePlayer,
+        side_bet: SideBet,
+        bet: u32,
+    ) -> Result<(), BlackjackGameError> {
+        let Some((_, paytable)) = self.side_bets.iter().find(|(s, _)| *s == side_bet) else {
+            return Err(BlackjackGameError {
+                message: format!("{side_bet} is not offered at this table"),
+            });
+        };
+        if bet == 0 {
+            return Err(BlackjackGameError {
+                message: "Side bet must be a positive amount".to_string(),
+            });
+        } else if self.balance < paytable.max_payout() * bet as f32 {
+            return Err(BlackjackGameError {
+                message: "Insufficient table balance to payout side bet".to_string(),
+            });
+        }
+        player.place_side_bet(side_bet, bet)
+    }
+
+    /// Settles the side bets `player` placed that settle at the deal if `at_deal` is true, or once the dealer has finished
+    /// their hand otherwise. Winning side bets are paid and the rest collected, each result is recorded in the hand history
+    /// and returned
+    fn settle_side_bets(&mut self, player: &mut ConsolePlayer, at_deal: bool) -> Vec<String> {
+        let (settling, pending) = std::mem::take(&mut player.side_bets)
+            .into_iter()
+            .partition(|(side_bet, _)| side_bet.settles_at_deal() == at_deal);
+        player.side_bets = pending;
+
+        let mut messages = vec![];
+        for (side_bet, bet) in settling {
+            let paytable = &self
+                .side_bets
+                .iter()
+                .find(|(s, _)| *s == side_bet)
+                .expect("side bets are only placed when offered")
+                .1;
+            let outcome = side_bet
+                .outcome(
+                    &player.bj_hand.hand[0],
+                    self.dealers_hand.up_card(),
+                    self.dealers_hand.cards(),
+                )
+                .and_then(|o| paytable.payout(o).map(|multiplier| (o, multiplier)));
+
+            let net = match outcome {
+                Some((_, multiplier)) => {
+                    let winnings = multiplier * bet as f32;
+                    player.balance += winnings + bet as f32;
+                    self.balance -= winnings;
+                    winnings
+                }
+                None => {
+                    self.balance += bet as f32;
+                    -(bet as f32)
+                }
+            };
+            player.stats.record_side_bet(&side_bet.to_string(), net);
+            let settlement = SideBetSettlement {
+                side_bet,
+                bet,
+                outcome: outcome.map(|(o, _)| o.to_string()),
+                net,
+                multiplier: outcome.map(|(_, multiplier)| multiplier),
+            };
+            messages.push(settlement.to_string());
+            self.history.side_bets.push(settlement);
+        }
+        messages
+    }
+
+    /// Returns the numbered options available to `player` for their current hand under the rules of the tables variant.
+    /// The method panics if the players current hand has busted or the player has not placed any bets
+    pub fn playing_options(&self, player: &ConsolePlayer) -> HashMap<i32, Action> {
+        // Before playing their hand the player decides whether to insure it against the dealers ace
+        if self.insurance_open {
+            return number_options(&[Action::Insurance, Action::NoInsurance]);
+        }
+
+        let options = player.get_playing_options();
+        match self.variant {
+            GameVariant::Standard | GameVariant::DoubleExposure => {
+                let mut options = options;
+                if self.rules.late_surrender && player.bj_hand.can_surrender(player.hand_idx) {
+                    options.insert(options.len() as i32 + 1, Action::Surrender);
+                }
+                options
+            }
+            GameVariant::Spanish21 => {
+                let hand_idx = player.hand_idx;
+                let bj_hand = &player.bj_hand;
+                // A doubled hand has received its card, so can only stand or be rescued
+                if bj_hand.doubled[hand_idx] {
+                    return number_options(&[Action::Stand, Action::Rescue]);
+                }
+
+                let mut spanish_options = vec![Action::Stand, Action::Hit];
+                if options.values().any(|o| *o == Action::Split) {
+                    spanish_options.push(Action::Split);
+                }
+                if bj_hand.hand[hand_idx].len() >= 2
+                    && player.balance >= bj_hand.bets[hand_idx] as f32
+                {
+                    spanish_options.push(Action::DoubleDown);
+                }
+                if bj_hand.can_surrender(hand_idx) {
+                    spanish_options.push(Action::Surrender);
+                }
+                number_options(&spanish_options)
+            }
+            GameVariant::Switch => {
+                // Before playing either hand the player decides whether to switch their second cards
+                if !self.switch_decided {
+                    return number_options(&[Action::Switch, Action::Keep]);
+                }
+
+                let hand_idx = player.hand_idx;
+                let bj_hand = &player.bj_hand;
+                let mut switch_options = vec![Action::Stand, Action::Hit];
+                if options.values().any(|o| *o == Action::Split) {
+                    switch_options.push(Action::Split);
+                }
+                if bj_hand.hand[hand_idx].len() == 2
+                    && player.balance >= bj_hand.bets[hand_idx] as f32
+                {
+                    switch_options.push(Action::DoubleDown);
+                }
+                number_options(&switch_options)
+            }
+            GameVariant::FreeBet => {
+                let hand_idx = player.hand_idx;
+                let bj_hand = &player.bj_hand;
+                let bet = bj_hand.bets[hand_idx] as f32;
+                let mut free_bet_options = vec![Action::Stand, Action::Hit];
+                // Every pair other than tens is split for free, tens may still be split with the players own money
+                if bj_hand.can_split(hand_idx) && bj_hand.hand.len() < 4 {
+                    if bj_hand.hand[hand_idx][0].val != 10 {
+                        free_bet_options.push(Action::FreeSplit);
+                    } else if player.balance >= bet {
+                        free_bet_options.push(Action::Split);
+                    }
+                }
+                // Hard 9, 10 and 11 are doubled for free, any other two cards may be doubled with the players own money
+                if bj_hand.hand[hand_idx].len() == 2 {
+                    let values = &bj_hand.hand_values[hand_idx];
+                    if values.len() == 1 && (9..=11).contains(&values[0]) {
+                        free_bet_options.push(Action::FreeDouble);
+                    } else if player.balance >= bet {
+                        free_bet_options.push(Action::DoubleDown);
+                    }
+                }
+                number_options(&free_bet_options)
+            }
+            GameVariant::Pontoon => {
+                let hand_idx = player.hand_idx;
+                let bj_hand = &player.bj_hand;
+                let mut pontoon_options = vec![Action::Twist];
+                if compute_optimal_hand(&bj_hand.hand_values[hand_idx])
+                    >= self.variant.minimum_stand()
+                {
+                    pontoon_options.push(Action::Stick);
+                }
+                // Cards can be bought until the player first twists on the hand
+                if !bj_hand.twisted[hand_idx]
+                    && player.balance >= bj_hand.original_bets[hand_idx] as f32
+                {
+                    pontoon_options.push(Action::Buy);
+                }
+                if options.values().any(|o| *o == Action::Split) {
+                    pontoon_options.push(Action::Split);
+                }
+                number_options(&pontoon_options)
+            }
+        }
+    }
+
+    /// Buys another card for `player`'s current hand in Pontoon, raising the hands bet by the bet it started with.
+    /// Unlike doubling down the hand stays in play after the card is dealt
+    pub fn buy(&mut self, player: &mut ConsolePlayer) {
+        player.buy(player.bj_hand.original_bets[player.hand_idx]);
+        self.hit(player);
+    }
+
+    /// Twists another card for `player`'s current hand in Pontoon, after which no more cards can be bought for it
+    fn twist(&mut self, player: &mut ConsolePlayer) {
+        player.bj_hand.twisted[player.hand_idx] = true;
+        self.hit(player);
+    }
+
+    /// Checks whether the dealer has blackjack once the player has been dealt their cards, settling every hand if they do.
+    /// Otherwise a lone player blackjack is paid straight away, unless the dealer has yet to draw their second card
+    fn peek(&mut self, player: &mut ConsolePlayer) {
+        let hole_card_late = self.hole_card.deals_hole_card_late();
+        let n_hands = player.bets().len();
+        if !hole_card_late && self.dealers_hand.is_blackjack() {
+            // Display state of table, no need to keep dealers hole card hidden
+            if !self.quiet {
+                println!("{:-<80}", "");
+                self.dealers_hand.display_hand(&self.card_style);
+                self.dealers_hand.display_hand_value();
+                println!("\n\n");
+                player.bj_hand.display_hand(&self.card_style);
+                player.display_balance();
+                println!();
+            }
+
+            // Check if player has blackjack, every hand with a blackjack pushes unless the dealer wins ties, the rest lose
+            let mut results = vec![];
+            for hand in 0..n_hands {
+                let bet = player.bj_hand.lose_bet(hand);
+                let natural_result = self
+                    .variant
+                    .hand_result(HandRank::Natural, HandRank::Natural);
+                let result =
+                    if player.bj_hand.is_ace_ten(hand) && natural_result == HandResult::Pushed {
+                        player.stats.record_blackjack();
+                        player.stats.record_push();
+                        player.balance += bet as f32;
+                        HandResult::Pushed
+                    } else {
+                        player.stats.record_loss();
+                        self.balance += bet as f32;
+                        HandResult::Lost
+                    };
+                self.history.settle(hand, bet, result);
+                results.push(match (result, n_hands > 1) {
+                    (HandResult::Pushed, true) => format!("you pushed bet #{}", hand + 1),
+                    (_, true) => format!("you lost bet #{}", hand + 1),
+                    (HandResult::Pushed, false) => String::from("you pushed"),
+                    (_, false) => String::from("you lost the bet"),
+                });
+            }
+            player.bj_hand.bets.clear();
+            self.message(format!("Dealer has blackjack: {}", results.join(", ")));
+        } else {
+            self.display_table(player);
+
+            // Check if player has a blackjack, with more than one hand blackjacks are paid once the player has seen every hand
+            if n_hands == 1 && player.has_blackjack() && hole_card_late {
+                // The blackjack can only be settled once the dealer has drawn their second card
+                player.skip_settled_hands();
+                self.message(String::from(
+                    "You got blackjack, waiting for the dealers second card",
+                ));
+            } else if n_hands == 1 && player.has_blackjack() {
+                let winnings =
+                    self.rules.blackjack_payout(self.variant) * (player.bets()[0] as f32);
+                self.balance -= winnings;
+                let bet = player.bj_hand.bets.pop().unwrap();
+                self.history
+                    .settle_with_net(0, bet, HandResult::Blackjack, winnings);
+                player.balance += winnings + (bet as f32);
+                player.stats.record_blackjack();
+                player.stats.record_win();
+                self.message(format!("You got blackjack, winnings: {:2.2}", winnings));
+            }
+        }
+    }
+
+    /// Completes the players insurance decision, taking insurance of half their bet if `insure` is true, then peeks for
+    /// blackjack. Insurance pays 2:1 if the dealer has blackjack and is lost otherwise
+    fn insure(&mut self, player: &mut ConsolePlayer, insure: bool) {
+        self.insurance_open = false;
+        if insure {
+            let insurance = player.bets()[0] as f32 / 2.0;
+            let net = if self.dealers_hand.is_blackjack() {
+                2.0 * insurance
+            } else {
+                -insurance
+            };
+            player.balance += net;
+            self.balance -= net;
+            player.stats.record_side_bet("Insurance", net);
+            self.history.insurance = Some(net);
+            self.message(if net > 0.0 {
+                format!("Insurance ${insurance:.2} pays ${net:.2}")
+            } else {
+                format!("Insurance ${insurance:.2}: lost")
+            });
+        }
+        self.peek(player);
+    }
+
+    /// Completes the players decision in Blackjack Switch, switching the second cards of their two hands if `switch` is true.
+    /// Any hand that is then a blackjack is paid immediately, unless the dealer has yet to draw their second card,
+    /// and the players hand_idx is moved past it.
+    pub fn switch(&mut self, player: &mut ConsolePlayer, switch: bool) {
+        self.switch_decided = true;
+        if switch {
+            player.bj_hand.switch_second_cards();
+        }
+
+        let mut messages = vec![];
+        for hand in 0..2 {
+            if player.bj_hand.is_natural(hand) && !self.hole_card.deals_hole_card_late() {
+                let bet = player.bj_hand.lose_bet(hand);
+                let winnings = self.rules.blackjack_payout(self.variant) * bet as f32;
+                self.balance -= winnings;
+                player.balance += winnings + bet as f32;
+                self.history
+                    .settle_with_net(hand, bet, HandResult::Blackjack, winnings);
+                player.stats.record_blackjack();
+                player.stats.record_win();
+                messages.push(format!(
+                    "You got blackjack on hand #{}, winnings: {:2.2}",
+                    hand + 1,
+                    winnings
+                ));
+            }
+        }
+        self.display_table(player);
+        for message in messages {
+            self.message(message);
+        }
+        player.skip_settled_hands();
+    }
+
+    /// Forfeits half the bet of `player`'s current hand and returns the other half to the player. Used both for late
+    /// surrender and to rescue a doubled hand, in which case the player forfeits their original bet and keeps the double.
+    /// Increases the players hand_idx by 1 to signal the hand is finished.
+    pub fn surrender(&mut self, player: &mut ConsolePlayer) {
+        let hand = player.hand_idx;
+        let bet = player.lose_bet();
+        let refund = bet as f32 / 2.0;
+        player.balance += refund;
+        self.balance += bet as f32 - refund;
+        self.history.settle(hand, bet, HandResult::Surrendered);
+        player.stats.record_loss();
+
+        self.display_table(player);
+        self.message(format!("You surrendered, ${refund:.2} was returned"));
+    }
+
+    /// Compares the value `players_total` of `player`'s hand at index `hand_idx` with the dealers final value `dealers_total`,
+    /// ranking both hands by the rules of the tables variant.
+    /// Returns the result of the hand, the amount won on top of the stake (or the amount lost if negative),
+    /// and the name of any bonus that was paid. Winnings are paid on the whole bet, but only the players own stake can be lost
+    fn settle_hand(
+        &self,
+        player: &ConsolePlayer,
+        hand_idx: usize,
+        players_total: u8,
+        dealers_total: u8,
+    ) -> (HandResult, f32, Option<&'static str>) {
+        let bet = player.bets()[hand_idx] as f32;
+        let players_rank =
+            self.variant
+                .hand_rank(&player.bj_hand.hand[hand_idx], players_total, false);
+        let dealers_rank = self.variant.hand_rank(
+            self.dealers_hand.cards(),
+            dealers_total,
+            self.dealers_hand.is_blackjack(),
+        );
+        match self.variant.hand_result(players_rank, dealers_rank) {
+            HandResult::Won => match self.variant.bonus(
+                &player.bj_hand.hand[hand_idx],
+                players_total,
+                player.bj_hand.doubled[hand_idx],
+            ) {
+                Some((multiplier, bonus)) => (HandResult::Won, bet * multiplier, Some(bonus)),
+                None => (HandResult::Won, bet, None),
+            },
+            HandResult::Pushed => (HandResult::Pushed, 0.0, None),
+            result => (result, -(player.bj_hand.stake(hand_idx) as f32), None),
+        }
+    }
+
+    /// Refunds every bet lost to a dealer blackjack beyond the players original bets, i.e. doubles and splits, under the
+    /// original bets only rule. The refund is taken off the net of the last hands lost in the hand history, and returned
+    fn refund_original_bets_only(&mut self) -> u32 {
+        let settlements = &mut self.history.settlements;
+        let lost: u32 = settlements
+            .iter()
+            .filter(|s| s.result == HandResult::Lost)
+            .map(|s| s.bet)
+            .sum();
+        let not_lost: u32 = settlements
+            .iter()
+            .filter(|s| s.result != HandResult::Lost)
+            .map(|s| s.bet)
+            .sum();
+        let original = self
+            .history
+            .bets
+            .iter()
+            .sum::<u32>()
+            .saturating_sub(not_lost);
+        let refund = lost.saturating_sub(original);
+
+        let mut remaining = refund as f32;
+        settlements.sort_by_key(|s| s.hand);
+        for settlement in settlements.iter_mut().rev() {
+            if settlement.result == HandResult::Lost && remaining > 0.0 {
+                let returned = remaining.min(-settlement.net);
+                settlement.net += returned;
+                remaining -= returned;
+            }
+        }
+        // Free bets were never lost by the player, so less than the full refund may have been returned
+        (refund as f32 - remaining) as u32
+    }
+
+    /// Displays the dealers hand while the player is acting, the hole card stays hidden unless the variant deals both
+    /// of the dealers cards face up, and the up card is hidden too if the variant deals both cards face down
+    fn display_dealers_hand(&self) {
+        if self.variant.dealer_cards_exposed() {
+            self.dealers_hand.display_hand(&self.card_style);
+            self.dealers_hand.display_hand_value();
+        } else if self.variant.dealer_cards_hidden() {
+            self.dealers_hand.display_hand_hidden(&self.card_style);
+        } else {
+            self.dealers_hand
+                .display_hand_without_hole(&self.card_style);
+        }
+    }
+
+    /// Prints the dealers hand, then `player`'s hands and balance while the player is acting, unless the table is quiet
+    fn display_table(&self, player: &ConsolePlayer) {
+        if self.quiet {
+            return;
+        }
+        println!("{}", "-".to_string().repeat(80));
+        self.display_dealers_hand();
+        println!("\n\n");
+        player.bj_hand.display_hand(&self.card_style);
+        player.display_balance();
+    }
+
+    /// Returns the dealers cards as the player sees them while acting, a card dealt face down is None
+    pub fn dealers_cards_shown(&self) -> Vec<Option<Rc<Card>>> {
+        let cards = self.dealers_hand.cards();
+        if self.variant.dealer_cards_exposed() {
+            cards.iter().cloned().map(Some).collect()
+        } else if self.variant.dealer_cards_hidden() {
+            vec![None; cards.len()]
+        } else if cards.len() == 1 {
+            vec![Some(Rc::clone(&cards[0]))]
+        } else {
+            // The hole card is the first card dealt to the dealer
+            cards
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (i > 0).then(|| Rc::clone(c)))
+                .collect()
+        }
+    }
+
+    /// Returns the Hi-Lo running count of every card the player has seen since the shoe was shuffled, i.e. the cards in
+    /// the discard tray other than burned cards and the cards in play other than those the dealer has face down
+    pub fn running_count(&self) -> i32 {
+        let hidden: i32 = self
+            .dealers_hand
+            .cards()
+            .iter()
+            .zip(self.dealers_cards_shown())
+            .filter(|(_, shown)| shown.is_none())
+            .map(|(card, _)| hi_lo_value(card))
+            .sum();
+        let seen: i32 = self
+            .deck
+            .seen_discards()
+            .iter()
+            .chain(self.deck.cards_in_play())
+            .map(|card| hi_lo_value(card))
+            .sum();
+        seen - hidden
+    }
+
+    /// Returns the running count divided by the number of decks still to be dealt
+    pub fn true_count(&self) -> f32 {
+        let cards_remaining = self.deck.remaining_cards().len() as f32;
+        let decks_remaining = f32::max(cards_remaining / self.deck.cards_per_deck() as f32, 0.5);
+        self.running_count() as f32 / decks_remaining
+    }
+
+    /// Returns the basic strategy play for `player`'s current hand out of `options`, or None if the table does not
+    /// play Standard blackjack, which the chart is for. Basic strategy never takes insurance
+    pub fn suggested_option(
+        &self,
+        player: &ConsolePlayer,
+        options: &HashMap<i32, Action>,
+    ) -> Option<Action> {
+        (self.variant == GameVariant::Standard).then(|| {
+            if self.insurance_open {
+                return Action::NoInsurance;
+            }
+            basic_strategy_option(
+                &player.bj_hand.hand[player.hand_idx],
+                &player.bj_hand.hand_values[player.hand_idx],
+                self.dealers_hand.up_card(),
+                options,
+            )
+        })
+    }
+
+    /// Prints the rules and limits of the table to the console
+    pub fn display_rules(&self) {
+        let maximum_bet = match self.maximum_bet {
+            Some(max) => format!("${max}"),
+            None => String::from("none"),
+        };
+        let soft_17 = if self.rules.dealer_hits_soft_17 {
+            "hits"
+        } else {
+            "stands"
+        };
+        // The house rules only change the payout of the variants paying 3:2, the others pay their own fixed odds
+        let blackjack_pays = match self.variant.blackjack_payout() {
+            VariantPayout::HouseRules => self.rules.blackjack_pays.to_string(),
+            VariantPayout::Fixed(payout) => format!("{payout}:1"),
+        };
+        let surrender = self.rules.late_surrender || self.variant == GameVariant::Spanish21;
+        println!("{:<20}{}", "Game:", self.variant);
+        println!("{:<20}{}", "Hole card:", self.hole_card);
+        println!("{:<20}{}", "Dealer soft 17:", soft_17);
+        println!("{:<20}{}", "Blackjack pays:", blackjack_pays);
+        println!(
+            "{:<20}{}",
+            "Late surrender:",
+            if surrender { "yes" } else { "no" }
+        );
+        println!("{:<20}{}", "Decks:", self.deck.n_decks());
+        println!("{:<20}${} to {}", "Bets:", self.minimum_bet, maximum_bet);
+    }
+
+    /// Returns the tables shoe, e.g. for inspecting the composition of the cards remaining to be dealt
+    pub fn deck(&self) -> &Deck {
+        &self.deck
+    }
+
+    /// Returns the history of the most recently finished round, if it has not already been taken
+    pub fn take_hand_history(&mut self) -> Option<HandHistory> {
+        self.completed_history.take()
+    }
+
+    /// Records the bet of `player`'s current hand as lost in the hand history, and collects the bet for the table.
+    /// Increases the players hand_idx by 1 to signal the hand is finished.
+    fn collect_lost_bet(&mut self, player: &mut ConsolePlayer) {
+        let hand = player.hand_idx;
+        // Only the players own stake is collected, any free part of the bet was never put up by the player
+        let stake = player.bj_hand.stake(hand);
+        let bet = player.lose_bet();
+        self.history
+            .settle_with_net(hand, bet, HandResult::Lost, -(stake as f32));
+        player.stats.record_loss();
+        self.balance += stake as f32;
+    }
+
+    /// Deals the single card to `player`'s current hand after it has been doubled, then either finishes the hand
+    /// or collects the bet if the hand busted
+    fn deal_doubled_card(&mut self, player: &mut ConsolePlayer) {
+        player.receive_card(self.deck.get_next_card().unwrap());
+        player.compute_hand_value();
+
+        if !player.busted() {
+            // Spanish 21 keeps the doubled hand in play so that it can be rescued
+            if self.variant == GameVariant::Spanish21 {
+                self.display_table(player);
+            } else {
+                player.stand();
+            }
+        } else {
+            self.display_table(player);
+            self.message(String::from("Busted, you lost the bet"));
+            self.collect_lost_bet(player);
+        }
+    }
+
+    /// Doubles down on `player`'s current hand with the house funding the doubled amount, as in Free Bet Blackjack
+    pub fn free_double_down(&mut self, player: &mut ConsolePlayer) {
+        player.free_double_down();
+        self.deal_doubled_card(player);
+    }
+
+    /// Splits `player`'s current hand with the house funding the bet of the new hand, as in Free Bet Blackjack
+    pub fn free_split(&mut self, player: &mut ConsolePlayer) {
+        player.free_split(
+            self.deck.get_next_card().unwrap(),
+            self.deck.get_next_card().unwrap(),
+        );
+        self.display_table(player);
+    }
+
+    /// Takes a HashMap<i32, Action> of numbered options and prints the options formatted nicely, along with the key
+    /// that plays each option
+    pub fn display_playing_options(&self, options: &HashMap<i32, Action>, player: &ConsolePlayer) {
+        let display_tag = if options.values().any(|o| *o == Action::Switch) {
+            String::from("Switch the second cards of your hands?")
+        } else if player.bj_hand.hand.len() >= 2 {
             format!("Your options (hand #{}):", player.hand_idx + 1)
         } else {
             String::from("You options: ")
@@ -25,10 +771,29 @@ impl ConsoleBlackjackTable {
         println!();
         println!("{}", display_tag);
         for i in 1..=(options.len() as i32) {
-            println!("\t{}: {}", i, &options[&i]);
+            println!("\t{}: {} [{}]", i, options[&i], options[&i].key());
         }
     }
 
+    /// Displays the state of the game at the end of a hand with `result_messages`, or keeps the messages and the
+    /// winnings for `take_messages` if the table is quiet
+    fn report_end_of_hand(
+        &mut self,
+        player: &ConsolePlayer,
+        mut result_messages: Vec<String>,
+        winnings: f32,
+    ) {
+        if !self.quiet {
+            self.display_end_of_hand_state(player, result_messages, winnings);
+            return;
+        }
+        if self.dealers_hand.busted() {
+            result_messages.push(String::from("Dealer busted"));
+        }
+        result_messages.push(format!("Winnings: ${winnings:2.2}"));
+        self.messages.extend(result_messages);
+    }
+
     /// A method that will display the state of the game on the console at the end of a hand
     pub fn display_end_of_hand_state(
         &self,
@@ -37,10 +802,10 @@ impl ConsoleBlackjackTable {
         winnings: f32,
     ) {
         println!("{}", "-".to_string().repeat(80));
-        self.dealers_hand.display_hand();
+        self.dealers_hand.display_hand(&self.card_style);
         self.dealers_hand.display_hand_value();
         println!("\n\n");
-        player.display_hand();
+        player.display_hand(&self.card_style);
         player.display_balance();
 
         // For readability
@@ -54,38 +819,12 @@ impl ConsoleBlackjackTable {
         }
         println!("Winnings: ${winnings:2.2}");
     }
-
-    /// Takes a Player `player`, HashMap `options` of playing options and an i32 `option`, then selects and calls the method
-    /// that implements the correct logic for the given option. The method pancis if `option` is not in the HashMap `options`
-    fn get_playing_options(
-        &mut self,
-        player: &mut ConsolePlayer,
-        options: &HashMap<i32, String>,
-        option: i32,
-    ) -> Result<(), BlackjackGameError> {
-        match options[&option].as_str() {
-            "stand" => Ok(self.stand(player)),
-            "hit" => Ok(self.hit(player)),
-            "split" => Ok(self.split(player)),
-            "double down" => Ok(self.double_down(player)),
-            _ => Err(BlackjackGameError {
-                message: format!("{} is not a valid option", option),
-            }),
-        }
-    }
 }
 
 impl BlackjackTable<ConsolePlayer> for ConsoleBlackjackTable {
     /// Creates a new instance of a BlackjackTableCLI struct
     fn new(starting_balance: f32, n_decks: usize, n_shuffles: u32) -> Self {
-        let deck = Deck::new(n_decks);
-
-        ConsoleBlackjackTable {
-            deck,
-            balance: starting_balance,
-            dealers_hand: ConsoleDealersBlackjackHand::new(),
-            n_shuffles,
-        }
+        Self::with_variant(starting_balance, n_decks, n_shuffles, GameVariant::Standard)
     }
 
     /// Takes a Player struct, `player` and places a bet
@@ -95,42 +834,78 @@ impl BlackjackTable<ConsolePlayer> for ConsoleBlackjackTable {
                 message: "Bet must be a positive amount".to_string(),
             });
             // return Err("Bet must be a positive amount".to_string());
-        } else if self.balance < 1.5 * bet {
+        } else if bet < self.minimum_bet as f32 {
+            return Err(BlackjackGameError {
+                message: format!("Minimum bet is ${}", self.minimum_bet),
+            });
+        } else if self.maximum_bet.is_some_and(|max| bet > max as f32) {
+            return Err(BlackjackGameError {
+                message: format!("Maximum bet is ${}", self.maximum_bet.unwrap_or_default()),
+            });
+        }
+
+        // Every bet is placed once for each hand the variant deals, e.g. the two hands of Blackjack Switch
+        let n_hands = self.variant.hands_per_bet() as f32;
+        if self.balance < 1.5 * bet * n_hands {
             return Err(BlackjackGameError {
                 message: "Insufficient table balance to payout bet".to_string(),
             });
+        } else if player.balance < bet * n_hands {
+            return Err(BlackjackGameError {
+                message: "Insufficient funds to place that bet".to_string(),
+            });
         }
-        player.place_bet(bet)
+        for _ in 0..self.variant.hands_per_bet() {
+            player.place_bet(bet)?;
+        }
+        Ok(())
     }
 
     /// Takes a Player `player`, HashMap `options` of playing options and an i32 `option`, then selects and calls the method
-    /// that implements the correct logic for the given option. The method pancis if `option` is not in the HashMap `options`
+    /// that implements the correct logic for the given option. Returns an error if `option` is not one of the `options`
     fn play_option(
         &mut self,
         player: &mut ConsolePlayer,
-        options: &HashMap<i32, String>,
+        options: &HashMap<i32, Action>,
         option: i32,
     ) -> Result<(), BlackjackGameError> {
-        match options[&option].as_str() {
-            "stand" => Ok(self.stand(player)),
-            "hit" => Ok(self.hit(player)),
-            "split" => Ok(self.split(player)),
-            "double down" => Ok(self.double_down(player)),
-            _ => Err(BlackjackGameError {
+        let Some(&action) = options.get(&option) else {
+            return Err(BlackjackGameError {
                 message: format!("{} is not a valid option", option),
-            }),
+            });
+        };
+        // Grade the decision against basic strategy before the players hand changes, the chart only applies to standard rules
+        if let Some(optimal_option) = self.suggested_option(player, options) {
+            player.stats.record_decision(action == optimal_option);
         }
+        self.history.actions.push(RecordedAction {
+            hand: player.hand_idx,
+            action,
+        });
+
+        match action {
+            Action::Stand | Action::Stick => self.stand(player),
+            Action::Hit => self.hit(player),
+            Action::Twist => self.twist(player),
+            Action::Split => self.split(player),
+            Action::DoubleDown => self.double_down(player),
+            Action::Surrender | Action::Rescue => self.surrender(player),
+            Action::Switch => self.switch(player, true),
+            Action::Keep => self.switch(player, false),
+            Action::FreeDouble => self.free_double_down(player),
+            Action::FreeSplit => self.free_split(player),
+            Action::Buy => self.buy(player),
+            Action::Insurance => self.insure(player, true),
+            Action::NoInsurance => self.insure(player, false),
+        }
+        Ok(())
     }
 
     /// Takes a Player struct `player` and changes its state via its stand method
     fn stand(&self, player: &mut ConsolePlayer) {
         player.stand();
         if !player.turn_is_over() {
-            println!("{}", "-".to_string().repeat(80));
-            self.dealers_hand.display_hand_without_hole();
-            println!("\n\n");
-            player.bj_hand.display_hand();
-            player.display_balance();
+            self.display_table(player);
         }
     }
 
@@ -140,15 +915,17 @@ impl BlackjackTable<ConsolePlayer> for ConsoleBlackjackTable {
         player.receive_card(self.deck.get_next_card().unwrap());
         player.compute_hand_value();
 
-        println!("{}", "-".to_string().repeat(80));
-        self.dealers_hand.display_hand_without_hole();
-        println!("\n\n");
-        player.bj_hand.display_hand();
-        player.display_balance();
+        self.display_table(player);
 
         if player.busted() {
-            println!("Busted, you lost the bet");
-            self.balance += player.lose_bet() as f32;
+            self.message(String::from("Busted, you lost the bet"));
+            self.collect_lost_bet(player);
+        } else if self.variant == GameVariant::Pontoon
+            && player.bj_hand.hand[player.hand_idx].len() == 5
+        {
+            // A five card trick can not be improved, so the hand is finished
+            self.message(String::from("Five card trick"));
+            player.stand();
         }
     }
 
@@ -156,20 +933,7 @@ impl BlackjackTable<ConsolePlayer> for ConsoleBlackjackTable {
     fn double_down(&mut self, player: &mut ConsolePlayer) {
         // Call the double_down() method of the player, and deal them another card
         player.double_down();
-        player.receive_card(self.deck.get_next_card().unwrap());
-        player.compute_hand_value();
-
-        if !player.busted() {
-            player.stand();
-        } else {
-            println!("{}", "-".to_string().repeat(80));
-            self.dealers_hand.display_hand_without_hole();
-            println!("\n\n");
-            player.bj_hand.display_hand();
-            player.display_balance();
-            println!("Busted, you lost the bet");
-            self.balance += player.lose_bet() as f32;
-        }
+        self.deal_doubled_card(player);
     }
 
     /// Method to execute the logic for a player to split
@@ -178,11 +942,7 @@ impl BlackjackTable<ConsolePlayer> for ConsoleBlackjackTable {
             self.deck.get_next_card().unwrap(),
             self.deck.get_next_card().unwrap(),
         );
-        println!("{}", "-".to_string().repeat(80));
-        self.dealers_hand.display_hand_without_hole();
-        println!("\n\n");
-        player.bj_hand.display_hand();
-        player.display_balance();
+        self.display_table(player);
     }
 
     /// Implments the logic that deals the initial cards at the start of a hand, checks if
@@ -195,65 +955,85 @@ impl BlackjackTable<ConsolePlayer> for ConsoleBlackjackTable {
 
         // Check if deck needs to be shuffled
         if self.deck.shuffle_flag {
-            println!("Shuffling...");
+            self.message(String::from("Shuffling..."));
             self.deck.shuffle(self.n_shuffles);
+            if let Some(penetration) = self.deck.last_penetration() {
+                self.message(format!("Penetration reached: {:.1}%", 100.0 * penetration));
+            }
         }
 
-        // Deal cards to player and dealer
-        player.receive_card(self.deck.get_next_card().unwrap());
+        self.round += 1;
+        self.history = HandHistory {
+            variant: self.variant,
+            hole_card: self.hole_card,
+            rules: self.rules,
+            round: self.round,
+            shoe_id: self.deck.shoe_id(),
+            seat: 1,
+            player: player.name().to_string(),
+            bets: player.bets().clone(),
+            ..Default::default()
+        };
+
+        // Deal cards to every one of the players hands and the dealer, without a hole card the dealer only gets their up card
+        self.switch_decided = false;
+        let hole_card_late = self.hole_card.deals_hole_card_late();
+        let n_hands = player.bets().len();
+        for hand in 0..n_hands {
+            player
+                .bj_hand
+                .receive_card(self.deck.get_next_card().unwrap(), hand);
+        }
 
         self.dealers_hand
             .receive_card(self.deck.get_next_card().unwrap());
 
-        player.receive_card(self.deck.get_next_card().unwrap());
+        for hand in 0..n_hands {
+            player
+                .bj_hand
+                .receive_card(self.deck.get_next_card().unwrap(), hand);
+        }
 
-        self.dealers_hand
-            .receive_card(self.deck.get_next_card().unwrap());
+        if !hole_card_late {
+            self.dealers_hand
+                .receive_card(self.deck.get_next_card().unwrap());
+            self.dealers_hand.compute_hand_value();
+        }
 
-        player.compute_hand_value();
-        self.dealers_hand.compute_hand_value();
+        for hand in 0..n_hands {
+            player.bj_hand.compute_hand_value(hand);
+        }
 
-        // Check if dealer has blackjack or not, then perform the appropriate logic
-        println!("{:-<80}", "");
-        if self.dealers_hand.is_blackjack() {
-            // Display state of table, no need to keep dealers hole card hidden
-            self.dealers_hand.display_hand();
-            self.dealers_hand.display_hand_value();
-            println!("\n\n");
-            player.bj_hand.display_hand();
-            player.display_balance();
-            println!();
-
-            // Check if player has blackjack
-            let mut result_str = String::from("Dealer has blackjack: ");
-            if player.has_blackjack() {
-                result_str.push_str("you pushed");
-                player.balance += player.bj_hand.bets.pop().unwrap() as f32;
-            } else {
-                result_str.push_str("you lost the bet");
-                self.balance += player.bj_hand.bets.pop().unwrap() as f32;
-            }
-            println!("{result_str}");
+        // Insurance is offered against an ace before the dealer peeks, so the peek waits for the players decision
+        self.insurance_open = !hole_card_late
+            && self.variant.offers_insurance()
+            && n_hands == 1
+            && self.dealers_hand.up_card().rank == "A"
+            && !player.has_blackjack()
+            && player.balance >= player.bets()[0] as f32 / 2.0;
+        if self.insurance_open {
+            self.display_table(player);
+            self.message(String::from("The dealer shows an ace, insurance is open"));
         } else {
-            self.dealers_hand.display_hand_without_hole();
-            println!("\n\n");
-            player.bj_hand.display_hand();
-            player.display_balance();
-
-            // Check if player has a blackjack
-            if player.has_blackjack() {
-                let winnings = 1.5 * (player.bets()[0] as f32);
-                self.balance -= winnings;
-                player.balance += winnings + (player.bj_hand.bets.pop().unwrap() as f32);
-                println!("You got blackjack, winnings: {:2.2}", winnings);
-            }
+            self.peek(player);
+        }
+
+        // Side bets decided by the cards just dealt are settled straight away
+        for msg in self.settle_side_bets(player, true) {
+            self.message(msg);
         }
     }
 
     /// A method for computing and returning the optimal hand for the dealer at the end of a hand of blackjack.
     /// The dealers draws cards according to the rules of blackjack, then the optimal hand once a hand with a value of no less than 17 is achieved
     fn get_dealers_optimal_final_hand(&mut self) -> u8 {
-        self.dealers_hand.compute_optimal_final_hand(&mut self.deck)
+        // Without a hole card the dealers second card is only drawn now that the player has acted
+        if self.dealers_hand.cards().len() == 1 {
+            self.dealers_hand
+                .receive_hole_card(self.deck.get_next_card().unwrap());
+        }
+        self.dealers_hand
+            .compute_optimal_final_hand(&mut self.deck, self.rules.dealer_hits_soft_17)
     }
 
     /// This method will complete a hand of blackjack, it will check `player` optimal hand(s) against the dealer and payout bets accordingly
@@ -263,51 +1043,372 @@ impl BlackjackTable<ConsolePlayer> for ConsoleBlackjackTable {
         // if the player has no remaining bets then, just skip to reseting dealer/player
         if let Some(players_optimal_hands) = player.get_optimal_hands() {
             let dealers_optimal_hand = self.get_dealers_optimal_final_hand();
+            self.settle_side_bets(player, false);
+            // The dealer can only have blackjack here when their second card was drawn after the player acted
+            let dealer_blackjack = self.dealers_hand.is_blackjack();
+            let multiple_hands = player.bets().len() > 1;
             let mut winnings: f32 = 0.0;
-            let mut refunded_bets: f32 = 0.0;
-            let result_messages = if player.bets().len() > 1 {
-                let mut res = vec![];
-
-                for (i, bet) in player.bets().iter().enumerate() {
-                    if *bet > 0 {
-                        if dealers_optimal_hand > 21
-                            || players_optimal_hands[&i] > dealers_optimal_hand
-                        {
-                            res.push(format!("You won bet #{}: ${}", i + 1, *bet));
-                            self.balance -= *bet as f32;
-                            winnings += *bet as f32;
-                        } else if players_optimal_hands[&i] == dealers_optimal_hand {
-                            res.push(format!("You pushed bet #{}: ${}", i + 1, *bet));
-                            refunded_bets += *bet as f32;
-                        } else {
-                            self.balance += *bet as f32;
-                            res.push(format!("You lost bet #{}: ${}", i + 1, *bet))
+            let mut returned_bets: f32 = 0.0;
+            let mut result_messages = vec![];
+
+            // The bets are copied as settling a hand records its result in the players stats
+            let bets = player.bets().clone();
+            for (i, bet) in bets.iter().enumerate() {
+                // Hands without a bet have already been settled i.e. busted, surrendered or paid a blackjack
+                if *bet == 0 {
+                    let msg = match self.history.settlements.iter().find(|s| s.hand == i) {
+                        Some(s) if s.result == HandResult::Blackjack => {
+                            format!("You got blackjack on bet #{}: ${}", i + 1, s.bet)
                         }
-                    } else {
-                        res.push(format!("You lost bet #{}: ${}", i + 1, *bet))
+                        Some(s) => format!("You {} bet #{}: ${}", s.result, i + 1, s.bet),
+                        None => format!("You lost bet #{}: ${}", i + 1, *bet),
+                    };
+                    result_messages.push(msg);
+                    continue;
+                }
+
+                let (result, net, bonus) = match (player.bj_hand.is_natural(i), dealer_blackjack) {
+                    // The same as a dealer blackjack found by peeking, the natural pushes unless the dealer wins ties
+                    (true, true) => match self
+                        .variant
+                        .hand_result(HandRank::Natural, HandRank::Natural)
+                    {
+                        HandResult::Pushed => (HandResult::Pushed, 0.0, None),
+                        result => (result, -(player.bj_hand.stake(i) as f32), None),
+                    },
+                    (true, false) => (
+                        HandResult::Blackjack,
+                        self.rules.blackjack_payout(self.variant) * *bet as f32,
+                        None,
+                    ),
+                    (false, true) => (HandResult::Lost, -(player.bj_hand.stake(i) as f32), None),
+                    (false, false) => {
+                        self.settle_hand(player, i, players_optimal_hands[&i], dealers_optimal_hand)
                     }
+                };
+                let mut msg = match (result, multiple_hands) {
+                    (HandResult::Blackjack, true) => {
+                        format!("You got blackjack on bet #{}: ${}", i + 1, *bet)
+                    }
+                    (HandResult::Won, true) => format!("You won bet #{}: ${}", i + 1, *bet),
+                    (HandResult::Pushed, true) => format!("You pushed bet #{}: ${}", i + 1, *bet),
+                    (_, true) => format!("You lost bet #{}: ${}", i + 1, *bet),
+                    (HandResult::Blackjack, false) => String::from("You got blackjack"),
+                    (HandResult::Won, false) => String::from("You won the bet"),
+                    (HandResult::Pushed, false) => String::from("You pushed"),
+                    (_, false) => String::from("You lost the bet"),
+                };
+                if let Some(bonus) = bonus {
+                    msg.push_str(&format!(", {bonus} bonus pays ${net:.2}"));
                 }
-                res
-            } else {
-                if dealers_optimal_hand > 21 || players_optimal_hands[&0] > dealers_optimal_hand {
-                    self.balance -= player.bets()[0] as f32;
-                    winnings += player.bets()[0] as f32;
-                    vec![String::from("You won the bet")]
-                } else if players_optimal_hands[&0] == dealers_optimal_hand {
-                    refunded_bets += player.bets()[0] as f32;
-                    vec![String::from("You pushed")]
-                } else {
-                    self.balance += player.bets()[0] as f32;
-                    vec![String::from("You lost the bet")]
+                result_messages.push(msg);
+
+                if result == HandResult::Blackjack {
+                    player.stats.record_blackjack();
                 }
-            };
+                // Only the players own stake is returned or collected, the house keeps any free part of the bet
+                let stake = player.bj_hand.stake(i) as f32;
+                match result {
+                    HandResult::Won | HandResult::Blackjack => {
+                        self.balance -= net;
+                        winnings += net;
+                        returned_bets += stake;
+                        player.stats.record_win();
+                    }
+                    HandResult::Pushed => {
+                        returned_bets += stake;
+                        player.stats.record_push();
+                    }
+                    _ => {
+                        self.balance += stake;
+                        player.stats.record_loss();
+                    }
+                }
+                self.history.settle_with_net(i, *bet, result, net);
+            }
 
-            self.display_end_of_hand_state(&player, result_messages, winnings);
-            player.balance += 2.0 * winnings;
-            player.balance += refunded_bets;
+            if dealer_blackjack && self.hole_card == HoleCardRule::NoHoleCardObo {
+                let refund = self.refund_original_bets_only();
+                if refund > 0 {
+                    self.balance -= refund as f32;
+                    returned_bets += refund as f32;
+                    result_messages.push(format!(
+                        "Original bets only: ${refund} of doubles and splits returned"
+                    ));
+                }
+            }
+
+            result_messages.extend(self.history.side_bets.iter().map(|s| s.to_string()));
+            self.report_end_of_hand(player, result_messages, winnings);
+            player.balance += winnings + returned_bets;
+        } else if !player.side_bets.is_empty() {
+            // Side bets settled on the dealers final hand still need the dealer to finish their hand
+            self.get_dealers_optimal_final_hand();
+            self.settle_side_bets(player, false);
+            let result_messages = self
+                .history
+                .side_bets
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+            self.report_end_of_hand(player, result_messages, 0.0);
         };
 
+        // Complete the record of the round before the hands are cleared and the cards in play are discarded
+        self.history.cards = self
+            .deck
+            .cards_in_play()
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        self.history.player_hands = player
+            .bj_hand
+            .hand
+            .iter()
+            .map(|h| h.iter().map(|c| c.to_string()).collect())
+            .collect();
+        self.history.dealer_hand = self
+            .dealers_hand
+            .cards()
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        self.history.settlements.sort_by_key(|s| s.hand);
+        self.completed_history = Some(std::mem::take(&mut self.history));
+
+        player.record_round();
+        self.deck.finish_round();
         self.dealers_hand.reset();
         player.reset();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::console::quitting_option;
+    use crate::strategy::hi_lo_value;
+    use crate::table_from_config;
+    use crate::testing::{play_round, results, stacked_table};
+
+    /// Returns the Hi-Lo count of `cards`
+    fn count(cards: &[Rc<Card>]) -> i32 {
+        cards.iter().map(|c| hi_lo_value(c)).sum()
+    }
+
+    #[test]
+    fn running_count_ignores_burned_cards() {
+        let config = GameConfig {
+            n_decks: 1,
+            burn_cards: 5,
+            seed: Some(3),
+            ..GameConfig::default()
+        };
+        let mut table = table_from_config(&config, config.variant.build_deck(config.n_decks));
+        table.set_quiet(true);
+        let mut player = ConsolePlayer::new(String::from("Player"), 500.0);
+
+        table.place_bet(&mut player, 10.0).unwrap();
+        table.deal_hand(&mut player);
+        let burned = table.deck().discard_tray().to_vec();
+        assert_eq!(burned.len(), 5);
+        assert_ne!(
+            count(&burned),
+            0,
+            "the burned cards should change the count"
+        );
+        assert!(table.deck().seen_discards().is_empty());
+        let shown: i32 = table
+            .dealers_cards_shown()
+            .iter()
+            .flatten()
+            .map(|c| hi_lo_value(c))
+            .sum();
+        assert_eq!(
+            table.running_count(),
+            count(&player.bj_hand.hand[0]) + shown
+        );
+
+        while !player.turn_is_over() {
+            let options = table.playing_options(&player);
+            table
+                .play_option(&mut player, &options, quitting_option(&options))
+                .unwrap();
+        }
+        table.finish_hand(&mut player);
+        let history = table.take_hand_history().unwrap();
+        assert_eq!(table.deck().seen_discards().len(), history.cards.len());
+        assert_eq!(table.running_count(), count(table.deck().seen_discards()));
+    }
+
+    #[test]
+    fn true_count_divides_by_the_decks_left_of_the_variant() {
+        let config = GameConfig {
+            variant: GameVariant::Spanish21,
+            n_decks: 2,
+            seed: Some(1),
+            ..GameConfig::default()
+        };
+        let mut table = table_from_config(&config, config.variant.build_deck(config.n_decks));
+        table.set_quiet(true);
+        let mut player = ConsolePlayer::new(String::from("Player"), 500.0);
+
+        table.place_bet(&mut player, 10.0).unwrap();
+        table.deal_hand(&mut player);
+        assert_ne!(table.running_count(), 0);
+        let decks_remaining = table.deck().remaining_cards().len() as f32 / 48.0;
+        assert_eq!(
+            table.true_count(),
+            table.running_count() as f32 / decks_remaining
+        );
+    }
+
+    /// Returns a config for the hole card rule `hole_card`
+    fn no_hole_card(hole_card: HoleCardRule) -> GameConfig {
+        GameConfig {
+            hole_card,
+            ..GameConfig::default()
+        }
+    }
+
+    #[test]
+    fn dealer_blackjack_takes_doubles_unless_original_bets_only() {
+        // The player doubles 11 into 20, then the dealers second card makes blackjack
+        let cards = "H6 S10 C5 D9 SA";
+        let (mut table, mut player) = stacked_table(cards, &no_hole_card(HoleCardRule::NoHoleCard));
+        let history = play_round(&mut table, &mut player, &[Action::DoubleDown]);
+        assert_eq!(results(&history), vec![(HandResult::Lost, -20.0)]);
+        assert_eq!(player.balance, 480.0);
+
+        let (mut table, mut player) =
+            stacked_table(cards, &no_hole_card(HoleCardRule::NoHoleCardObo));
+        let history = play_round(&mut table, &mut player, &[Action::DoubleDown]);
+        assert_eq!(results(&history), vec![(HandResult::Lost, -10.0)]);
+        assert_eq!(player.balance, 490.0);
+    }
+
+    #[test]
+    fn dealer_blackjack_takes_splits_unless_original_bets_only() {
+        // The player splits eights into 17 and 18, then the dealers second card makes blackjack
+        let cards = "H8 S10 D8 C9 C10 SA";
+        let actions = [Action::Split, Action::Stand, Action::Stand];
+        let (mut table, mut player) = stacked_table(cards, &no_hole_card(HoleCardRule::NoHoleCard));
+        let history = play_round(&mut table, &mut player, &actions);
+        assert_eq!(
+            results(&history),
+            vec![(HandResult::Lost, -10.0), (HandResult::Lost, -10.0)]
+        );
+        assert_eq!(player.balance, 480.0);
+
+        let (mut table, mut player) =
+            stacked_table(cards, &no_hole_card(HoleCardRule::NoHoleCardObo));
+        let history = play_round(&mut table, &mut player, &actions);
+        assert_eq!(
+            results(&history),
+            vec![(HandResult::Lost, -10.0), (HandResult::Lost, 0.0)]
+        );
+        assert_eq!(player.balance, 490.0);
+    }
+
+    #[test]
+    fn late_dealer_blackjack_settles_a_natural_like_a_peek() {
+        // A natural pushes a dealer blackjack in Standard, but the dealer wins every tie in Pontoon
+        let cards = "HA S10 DK SA";
+        for (variant, expected) in [
+            (GameVariant::Standard, (HandResult::Pushed, 0.0)),
+            (GameVariant::Pontoon, (HandResult::Lost, -10.0)),
+        ] {
+            for hole_card in [HoleCardRule::Peek, HoleCardRule::NoHoleCard] {
+                let config = GameConfig {
+                    variant,
+                    ..no_hole_card(hole_card)
+                };
+                let (mut table, mut player) = stacked_table(cards, &config);
+                let history = play_round(&mut table, &mut player, &[]);
+                assert_eq!(
+                    results(&history),
+                    vec![expected],
+                    "{variant} with {hole_card}"
+                );
+            }
+        }
+    }
+
+    /// Returns a config for the variant `variant`
+    fn playing(variant: GameVariant) -> GameConfig {
+        GameConfig {
+            variant,
+            ..GameConfig::default()
+        }
+    }
+
+    #[test]
+    fn spanish_21_pays_a_five_card_21_bonus() {
+        // The player draws 2-3-8 into a five card 21 against the dealers 18
+        let cards = "H2 SK D3 C8 C4 S5 H7";
+        let (mut table, mut player) = stacked_table(cards, &playing(GameVariant::Spanish21));
+        let actions = [Action::Hit, Action::Hit, Action::Hit, Action::Stand];
+        let history = play_round(&mut table, &mut player, &actions);
+        assert_eq!(results(&history), vec![(HandResult::Won, 15.0)]);
+        assert_eq!(player.balance, 515.0);
+    }
+
+    #[test]
+    fn spanish_21_rescues_a_doubled_hand_for_the_original_bet() {
+        // The player doubles 11 into 13 against the dealers 16 and rescues the hand
+        let cards = "H5 S9 D6 C7 H2";
+        let (mut table, mut player) = stacked_table(cards, &playing(GameVariant::Spanish21));
+        let actions = [Action::DoubleDown, Action::Rescue];
+        let history = play_round(&mut table, &mut player, &actions);
+        assert_eq!(results(&history), vec![(HandResult::Surrendered, -10.0)]);
+        assert_eq!(player.balance, 490.0);
+    }
+
+    #[test]
+    fn switch_pays_a_switched_blackjack_even_money_and_pushes_a_dealer_22() {
+        // Switching the second cards of A-5 and K-K makes a blackjack and 15, then the dealer draws 9-7-6 to 22
+        let cards = "SA SK C9 D5 HK C7 H6";
+        let (mut table, mut player) = stacked_table(cards, &playing(GameVariant::Switch));
+        let history = play_round(&mut table, &mut player, &[Action::Switch, Action::Stand]);
+        assert_eq!(
+            results(&history),
+            vec![(HandResult::Blackjack, 10.0), (HandResult::Pushed, 0.0)]
+        );
+        assert_eq!(player.balance, 510.0);
+    }
+
+    #[test]
+    fn double_exposure_loses_ties_except_two_naturals() {
+        let config = playing(GameVariant::DoubleExposure);
+        let (mut table, mut player) = stacked_table("HK S9 D8 C9", &config);
+        let history = play_round(&mut table, &mut player, &[Action::Stand]);
+        assert_eq!(results(&history), vec![(HandResult::Lost, -10.0)]);
+        assert_eq!(player.balance, 490.0);
+
+        let (mut table, mut player) = stacked_table("HA SA DK CK", &config);
+        let history = play_round(&mut table, &mut player, &[]);
+        assert_eq!(results(&history), vec![(HandResult::Pushed, 0.0)]);
+        assert_eq!(player.balance, 500.0);
+    }
+
+    #[test]
+    fn free_bet_loses_only_the_stake_of_a_free_double() {
+        // The player doubles 10 for free into 12 against the dealers 17
+        let cards = "H6 S10 D4 C7 H2";
+        let (mut table, mut player) = stacked_table(cards, &playing(GameVariant::FreeBet));
+        let history = play_round(&mut table, &mut player, &[Action::FreeDouble]);
+        assert_eq!(results(&history), vec![(HandResult::Lost, -10.0)]);
+        assert_eq!(player.balance, 490.0);
+    }
+
+    #[test]
+    fn free_bet_pushes_a_dealer_22() {
+        // The player doubles 10 for free into 17, then the dealer draws 10-6-6 to 22
+        let cards = "H6 S10 D4 C6 H7 H6";
+        let (mut table, mut player) = stacked_table(cards, &playing(GameVariant::FreeBet));
+        let history = play_round(&mut table, &mut player, &[Action::FreeDouble]);
+        assert_eq!(results(&history), vec![(HandResult::Pushed, 0.0)]);
+        assert_eq!(player.balance, 500.0);
+    }
+}
@@ -0,0 +1,476 @@
+use super::quitting_option;
+use crate::action::choose_option;
+use crate::card_style::{truncate, CardStyle};
+use crate::console::player::ConsolePlayer;
+use crate::console::table::ConsoleBlackjackTable;
+use crate::history::{HandHistory, HandHistoryWriter};
+use crate::profile::ProfileStore;
+use crate::side_bet::total;
+use crate::variant::HoleCardRule;
+use crate::{BlackjackTable, Card};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::collections::VecDeque;
+use std::io::{stdout, Write};
+use std::rc::Rc;
+use std::str::FromStr;
+
+/// The most messages kept in the action log
+const LOG_LENGTH: usize = 200;
+/// The width of the column each of the players hands is drawn in
+const HAND_WIDTH: u16 = 26;
+/// Returns the cards recorded in the hand history as `cards`, skipping any that can not be parsed
+fn recorded_cards(cards: &[String]) -> Vec<Rc<Card>> {
+    cards
+        .iter()
+        .filter_map(|c| Card::from_str(c).ok())
+        .map(Rc::new)
+        .collect()
+}
+
+/// The part of a round the game is waiting on the player for
+enum Phase {
+    /// The player is typing their bet
+    Betting(String),
+    /// The player is typing their bet on the side bet at this index of the tables side bets, an empty bet skips it
+    SideBet(usize, String),
+    /// The player is choosing how to play their hand
+    Playing,
+    /// The round just finished is shown until the player starts the next one
+    RoundOver(HandHistory),
+}
+
+/// What is drawn in the column of one of the players hands
+struct HandColumn {
+    title: String,
+    cards: String,
+    value: String,
+    bet: String,
+    result: String,
+}
+
+/// Puts the terminal into raw mode on the alternate screen, and restores the terminal when dropped
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> std::io::Result<RawTerminal> {
+        terminal::enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen, Hide)?;
+        Ok(RawTerminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        // Nothing more can be done if the terminal can not be restored
+        let _ = execute!(stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// A full-screen front-end for a blackjack game played in the terminal. The table is drawn in a fixed layout,
+/// the dealer above the players hands side by side, then the players balance and bets and a log of the actions taken.
+/// The game is played with the same table and player as the console game, with a key for every playing option
+pub struct TuiBlackjackGame {
+    table: ConsoleBlackjackTable,
+    player: ConsolePlayer,
+    minimum_bet: u32,
+    profiles: Option<ProfileStore>,
+    history: Option<HandHistoryWriter>,
+    log: VecDeque<String>,
+}
+
+impl TuiBlackjackGame {
+    /// Returns a new TuiBlackjackGame, if `profiles` is given the players profile is saved to it at the end of every round,
+    /// and if `history` is given every round is written to the hand history logs
+    pub fn new(
+        minimum_bet: u32,
+        player: ConsolePlayer,
+        table: ConsoleBlackjackTable,
+        profiles: Option<ProfileStore>,
+        history: Option<HandHistoryWriter>,
+    ) -> TuiBlackjackGame {
+        TuiBlackjackGame {
+            table,
+            player,
+            minimum_bet,
+            profiles,
+            history,
+            log: VecDeque::new(),
+        }
+    }
+
+    /// Adds `message` to the action log, dropping the oldest message once the log is full
+    fn log(&mut self, message: String) {
+        if self.log.len() == LOG_LENGTH {
+            self.log.pop_front();
+        }
+        self.log.push_back(message);
+    }
+
+    /// Moves the messages the table has kept since they were last taken into the action log
+    fn log_table_messages(&mut self) {
+        for message in self.table.take_messages() {
+            self.log(message);
+        }
+    }
+
+    /// Plays a game of blackjack until the player quits with Q or Esc. Quitting once a bet is placed plays the round out
+    /// standing on the players hands wherever the rules let them, so the bet is settled and saved before the game ends
+    pub fn play(&mut self) -> std::io::Result<()> {
+        self.table.set_quiet(true);
+        let _terminal = RawTerminal::enter()?;
+        self.log(format!(
+            "Welcome {}, place a bet to start",
+            self.player.name()
+        ));
+
+        let mut phase = Phase::Betting(String::new());
+        loop {
+            self.draw(&phase)?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if key.code == KeyCode::Esc
+                || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+            {
+                match phase {
+                    Phase::SideBet(..) => {
+                        self.table.deal_hand(&mut self.player);
+                        self.play_out_round();
+                    }
+                    Phase::Playing => self.play_out_round(),
+                    Phase::Betting(_) | Phase::RoundOver(_) => {}
+                }
+                break;
+            }
+
+            phase = match phase {
+                Phase::Betting(mut bet) => match key.code {
+                    KeyCode::Char(c) if c.is_ascii_digit() && bet.len() < 9 => {
+                        bet.push(c);
+                        Phase::Betting(bet)
+                    }
+                    KeyCode::Backspace => {
+                        bet.pop();
+                        Phase::Betting(bet)
+                    }
+                    KeyCode::Enter => self.place_bet(bet),
+                    KeyCode::Char('q' | 'Q') => break,
+                    _ => Phase::Betting(bet),
+                },
+                Phase::SideBet(idx, mut bet) => match key.code {
+                    KeyCode::Char(c) if c.is_ascii_digit() && bet.len() < 9 => {
+                        bet.push(c);
+                        Phase::SideBet(idx, bet)
+                    }
+                    KeyCode::Backspace => {
+                        bet.pop();
+                        Phase::SideBet(idx, bet)
+                    }
+                    KeyCode::Enter => self.place_side_bet(idx, bet),
+                    _ => Phase::SideBet(idx, bet),
+                },
+                Phase::Playing => self.play_key(key),
+                Phase::RoundOver(history) => match key.code {
+                    KeyCode::Enter | KeyCode::Char(' ' | 'n' | 'N') => {
+                        Phase::Betting(String::new())
+                    }
+                    KeyCode::Char('q' | 'Q') => break,
+                    _ => Phase::RoundOver(history),
+                },
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Places the bet `bet` typed by the player and deals the round, or logs why the bet could not be placed
+    fn place_bet(&mut self, bet: String) -> Phase {
+        let amount = match u32::from_str(&bet) {
+            Ok(n) if n >= self.minimum_bet => n,
+            _ => {
+                self.log(format!("Minimum bet is ${}", self.minimum_bet));
+                return Phase::Betting(String::new());
+            }
+        };
+        if let Err(e) = self.table.place_bet(&mut self.player, amount as f32) {
+            self.log(e.to_string());
+            return Phase::Betting(bet);
+        }
+
+        self.log(format!("Bet ${amount}"));
+        self.next_side_bet(0)
+    }
+
+    /// Places the bet `bet` typed by the player on the side bet at `idx` of the tables side bets, or logs why the bet could
+    /// not be placed. An empty bet skips the side bet
+    fn place_side_bet(&mut self, idx: usize, bet: String) -> Phase {
+        let side_bet = self.table.side_bets()[idx];
+        if !bet.is_empty() {
+            let side_bet_result = u32::from_str(&bet)
+                .map_err(|e| e.to_string())
+                .and_then(|n| {
+                    self.table
+                        .place_side_bet(&mut self.player, side_bet, n)
+                        .map_err(|e| e.to_string())
+                });
+            if let Err(e) = side_bet_result {
+                self.log(e);
+                return Phase::SideBet(idx, String::new());
+            }
+            self.log(format!("Bet ${bet} on {side_bet}"));
+        }
+        self.next_side_bet(idx + 1)
+    }
+
+    /// Returns the phase for the side bet at `idx` of the tables side bets, or deals the round once every side bet has
+    /// been offered
+    fn next_side_bet(&mut self, idx: usize) -> Phase {
+        if idx < self.table.side_bets().len() {
+            return Phase::SideBet(idx, String::new());
+        }
+        self.table.deal_hand(&mut self.player);
+        self.next_phase()
+    }
+
+    /// Plays out the round for a player who quit, standing wherever the rules let them, then finishes the round
+    fn play_out_round(&mut self) {
+        while !self.player.turn_is_over() {
+            let options = self.table.playing_options(&self.player);
+            self.table
+                .play_option(&mut self.player, &options, quitting_option(&options))
+                .expect("quitting option should be one of the players options");
+        }
+        self.next_phase();
+    }
+
+    /// Plays the option bound to the key `key` on the players current hand, keys that play no option are ignored
+    fn play_key(&mut self, key: KeyEvent) -> Phase {
+        let options = self.table.playing_options(&self.player);
+        let KeyCode::Char(c) = key.code else {
+            return Phase::Playing;
+        };
+        let Some(option) = choose_option(&options, &c.to_string()) else {
+            return Phase::Playing;
+        };
+
+        self.log(format!(
+            "Hand #{}: {}",
+            self.player.hand_idx + 1,
+            options[&option]
+        ));
+        if let Err(e) = self.table.play_option(&mut self.player, &options, option) {
+            self.log(format!("error: {e}"));
+        }
+        self.next_phase()
+    }
+
+    /// Returns the phase the round is in after the player has acted, finishing the round if the players turn is over
+    fn next_phase(&mut self) -> Phase {
+        self.log_table_messages();
+        if !self.player.turn_is_over() {
+            return Phase::Playing;
+        }
+
+        self.table.finish_hand(&mut self.player);
+        self.log_table_messages();
+        if let Some(profiles) = self.profiles.as_mut() {
+            profiles.update(self.player.to_profile());
+            if let Err(e) = profiles.save() {
+                self.log(format!("Error saving profile, {e}"));
+            }
+        }
+        let history = self.table.take_hand_history().unwrap_or_default();
+        if let Some(writer) = self.history.as_mut() {
+            if let Err(e) = writer.write(&history) {
+                self.log(format!("Error writing hand history, {e}"));
+            }
+        }
+        Phase::RoundOver(history)
+    }
+
+    /// Returns `cards` drawn on a single line in the tables card style, a card that is None is drawn face down.
+    /// ASCII-art cards do not fit the layout so are drawn on a single line as well
+    fn cards_str(&self, cards: &[Option<&Card>]) -> String {
+        let style = CardStyle {
+            ascii_art: false,
+            ..self.table.card_style()
+        };
+        style.cards(cards).remove(0)
+    }
+
+    /// Returns the dealers cards and their value as they are drawn in `phase`
+    fn dealer_lines(&self, phase: &Phase) -> (String, String) {
+        let cards = match phase {
+            Phase::Betting(_) | Phase::SideBet(..) => return (String::new(), String::new()),
+            Phase::Playing => self.table.dealers_cards_shown(),
+            Phase::RoundOver(history) => recorded_cards(&history.dealer_hand)
+                .into_iter()
+                .map(Some)
+                .collect(),
+        };
+
+        let cards_str = self.cards_str(&cards.iter().map(|c| c.as_deref()).collect::<Vec<_>>());
+        // The value is only shown once every card is face up
+        let value = match cards.iter().cloned().collect::<Option<Vec<Rc<Card>>>>() {
+            Some(shown) if shown.len() >= 2 => total(&shown).to_string(),
+            _ => String::from("?"),
+        };
+        (cards_str, value)
+    }
+
+    /// Returns what is drawn in the column of each of the players hands in `phase`
+    fn hand_columns(&self, phase: &Phase) -> Vec<HandColumn> {
+        match phase {
+            Phase::Betting(_) | Phase::SideBet(..) => vec![],
+            Phase::Playing => {
+                let bj_hand = &self.player.bj_hand;
+                (0..bj_hand.bets.len())
+                    .map(|i| HandColumn {
+                        title: match i == self.player.hand_idx {
+                            true => format!("> Hand #{}", i + 1),
+                            false => format!("  Hand #{}", i + 1),
+                        },
+                        cards: self.cards_str(
+                            &bj_hand.hand[i]
+                                .iter()
+                                .map(|c| Some(c.as_ref()))
+                                .collect::<Vec<_>>(),
+                        ),
+                        value: bj_hand.value_str(i).to_string(),
+                        bet: format!("${}", bj_hand.bet_str(i)),
+                        result: String::new(),
+                    })
+                    .collect()
+            }
+            Phase::RoundOver(history) => history
+                .player_hands
+                .iter()
+                .enumerate()
+                .map(|(i, hand)| {
+                    let settlement = history.settlements.iter().find(|s| s.hand == i);
+                    let cards = recorded_cards(hand);
+                    HandColumn {
+                        title: format!("  Hand #{}", i + 1),
+                        cards: self
+                            .cards_str(&cards.iter().map(|c| Some(c.as_ref())).collect::<Vec<_>>()),
+                        value: total(&cards).to_string(),
+                        bet: settlement.map_or(String::new(), |s| format!("${}", s.bet)),
+                        result: settlement
+                            .map_or(String::new(), |s| format!("{} {:+.2}", s.result, s.net)),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the key help shown on the bottom line of the screen in `phase`
+    fn help_line(&self, phase: &Phase) -> String {
+        match phase {
+            Phase::Betting(bet) => format!(
+                "Bet (minimum ${}): ${bet}_    [Enter] deal  [Backspace] delete  [Q] quit",
+                self.minimum_bet
+            ),
+            Phase::SideBet(idx, bet) => format!(
+                "Bet on {}: ${bet}_    [Enter] place, nothing to skip  [Backspace] delete  [Esc] quit",
+                self.table.side_bets()[*idx]
+            ),
+            Phase::Playing => {
+                let options = self.table.playing_options(&self.player);
+                let mut keys = vec![];
+                for i in 1..=(options.len() as i32) {
+                    let key = options[&i].key().to_ascii_uppercase();
+                    keys.push(format!("[{key}] {}", options[&i]));
+                }
+                keys.push(String::from("[Esc] quit"));
+                keys.join("  ")
+            }
+            Phase::RoundOver(_) => String::from("[Enter] next round  [Q] quit"),
+        }
+    }
+
+    /// Redraws the whole screen for `phase`
+    fn draw(&self, phase: &Phase) -> std::io::Result<()> {
+        let mut out = stdout();
+        let (width, height) = terminal::size()?;
+        let rule = "-".repeat(width as usize);
+        queue!(out, Clear(ClearType::All))?;
+        let mut put = |column: u16, row: u16, text: &str| -> std::io::Result<()> {
+            if row >= height || column >= width {
+                return Ok(());
+            }
+            let text = truncate(text, (width - column) as usize);
+            queue!(out, MoveTo(column, row), Print(text))
+        };
+
+        let rules = match self.table.hole_card_rule() {
+            HoleCardRule::Peek => self.table.variant().to_string(),
+            hole_card => format!("{} {hole_card}", self.table.variant()),
+        };
+        put(0, 0, &format!(" Blackjack - {rules}"))?;
+        put(0, 1, &rule)?;
+
+        // The dealer
+        let (dealers_cards, dealers_value) = self.dealer_lines(phase);
+        put(1, 2, "Dealer")?;
+        put(3, 3, &dealers_cards)?;
+        if !dealers_cards.is_empty() {
+            put(3, 4, &format!("Value: {dealers_value}"))?;
+        }
+        put(0, 6, &rule)?;
+
+        // The players hands, side by side
+        let columns = self.hand_columns(phase);
+        if columns.is_empty() {
+            put(3, 8, "Place your bet")?;
+        }
+        for (i, column) in columns.iter().enumerate() {
+            let x = 1 + i as u16 * HAND_WIDTH;
+            put(x, 7, &column.title)?;
+            put(x + 2, 8, &column.cards)?;
+            put(x + 2, 9, &format!("Value: {}", column.value))?;
+            put(x + 2, 10, &format!("Bet:   {}", column.bet))?;
+            put(x + 2, 11, &column.result)?;
+        }
+        put(0, 12, &rule)?;
+
+        // The bets and balance panel
+        let total_bet: u32 = match phase {
+            Phase::SideBet(..) | Phase::Playing => self.player.bets().iter().sum(),
+            Phase::RoundOver(history) => history.settlements.iter().map(|s| s.bet).sum(),
+            Phase::Betting(_) => 0,
+        };
+        put(
+            1,
+            13,
+            &format!(
+                "{:<10}${:<12.2}{:<10}${:<10}{:<8}{} cards",
+                "Balance:",
+                self.player.balance,
+                "Bet:",
+                total_bet,
+                "Shoe:",
+                self.table.deck().remaining_cards().len()
+            ),
+        )?;
+        put(0, 14, &rule)?;
+
+        // The action log fills the rest of the screen, newest message last
+        let log_rows = height.saturating_sub(17);
+        let skip = self.log.len().saturating_sub(log_rows as usize);
+        for (i, message) in self.log.iter().skip(skip).enumerate() {
+            put(1, 15 + i as u16, message)?;
+        }
+
+        put(0, height.saturating_sub(2), &rule)?;
+        put(1, height.saturating_sub(1), &self.help_line(phase))?;
+        out.flush()
+    }
+}
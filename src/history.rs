@@ -0,0 +1,220 @@
+use crate::action::Action;
+use crate::side_bet::SideBet;
+use crate::variant::{GameVariant, HoleCardRule, HouseRules};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// The default base path hand histories are written to, `.txt` and `.jsonl` extensions are added for each format
+pub const DEFAULT_HISTORY_PATH: &str = "hand_history";
+
+/// The outcome of a single hand of blackjack
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandResult {
+    Blackjack,
+    Won,
+    Pushed,
+    Lost,
+    Surrendered,
+}
+
+impl Display for HandResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let result = match self {
+            HandResult::Blackjack => "blackjack",
+            HandResult::Won => "won",
+            HandResult::Pushed => "pushed",
+            HandResult::Lost => "lost",
+            HandResult::Surrendered => "surrendered",
+        };
+        write!(f, "{result}")
+    }
+}
+
+/// A playing decision made by the player, `hand` is the index of the hand the decision was made on
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecordedAction {
+    pub hand: usize,
+    pub action: Action,
+}
+
+/// The settlement of a single hand, `net` is the amount the player won, negative if the bet was lost
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Settlement {
+    pub hand: usize,
+    pub bet: u32,
+    pub result: HandResult,
+    pub net: f32,
+}
+
+/// The settlement of a side bet, `outcome` is the winning outcome paid or None if the side bet lost
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SideBetSettlement {
+    pub side_bet: SideBet,
+    pub bet: u32,
+    pub outcome: Option<String>,
+    pub net: f32,
+    /// The multiple of the bet the paytable paid for `outcome`, missing from rounds recorded before it was kept
+    #[serde(default)]
+    pub multiplier: Option<f32>,
+}
+
+impl Display for SideBetSettlement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.outcome {
+            Some(outcome) => write!(
+                f,
+                "{} ${}: {} pays ${:.2}",
+                self.side_bet, self.bet, outcome, self.net
+            ),
+            None => write!(f, "{} ${}: lost", self.side_bet, self.bet),
+        }
+    }
+}
+
+/// A complete record of a single round of blackjack
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct HandHistory {
+    #[serde(default)]
+    pub variant: GameVariant,
+    #[serde(default)]
+    pub hole_card: HoleCardRule,
+    #[serde(default)]
+    pub rules: HouseRules,
+    pub round: u32,
+    pub shoe_id: u32,
+    pub seat: usize,
+    pub player: String,
+    pub bets: Vec<u32>,
+    /// Every card dealt from the shoe during the round, in the order it was dealt
+    pub cards: Vec<String>,
+    pub actions: Vec<RecordedAction>,
+    pub player_hands: Vec<Vec<String>>,
+    pub dealer_hand: Vec<String>,
+    pub settlements: Vec<Settlement>,
+    #[serde(default)]
+    pub side_bets: Vec<SideBetSettlement>,
+    /// The players net result on insurance, if they took it
+    #[serde(default)]
+    pub insurance: Option<f32>,
+}
+
+impl HandHistory {
+    /// Records the settlement of hand `hand` with final bet `bet`, computing the players net result from `result`.
+    /// The method panics if `result` is a blackjack, whose payout depends on the variant and house rules and so must be
+    /// recorded with `settle_with_net`
+    pub fn settle(&mut self, hand: usize, bet: u32, result: HandResult) {
+        let net = match result {
+            HandResult::Blackjack => {
+                panic!("a blackjack must be settled with the payout of the table")
+            }
+            HandResult::Won => bet as f32,
+            HandResult::Pushed => 0.0,
+            HandResult::Lost => -(bet as f32),
+            HandResult::Surrendered => -(bet as f32) / 2.0,
+        };
+        self.settle_with_net(hand, bet, result, net);
+    }
+
+    /// Records the settlement of hand `hand` with final bet `bet` where the players net result `net` is not
+    /// determined by `result` alone, e.g. a win paid at a bonus rate
+    pub fn settle_with_net(&mut self, hand: usize, bet: u32, result: HandResult, net: f32) {
+        self.settlements.push(Settlement {
+            hand,
+            bet,
+            result,
+            net,
+        });
+    }
+}
+
+impl Display for HandHistory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bets = self
+            .bets
+            .iter()
+            .map(|b| format!("${b}"))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let mut rules = match self.hole_card {
+            HoleCardRule::Peek => self.variant.to_string(),
+            hole_card => format!("{} {}", self.variant, hole_card),
+        };
+        if self.rules != HouseRules::default() {
+            rules.push_str(&format!(" ({})", self.rules));
+        }
+        writeln!(
+            f,
+            "*** {} - Round #{} - Shoe #{} - Seat {}: {} ***",
+            rules, self.round, self.shoe_id, self.seat, self.player
+        )?;
+        writeln!(f, "{:<10}{}", "Bets:", bets)?;
+        writeln!(f, "{:<10}{}", "Dealt:", self.cards.join(" "))?;
+        for action in &self.actions {
+            writeln!(
+                f,
+                "{:<10}hand #{} {}",
+                "Action:",
+                action.hand + 1,
+                action.action
+            )?;
+        }
+        writeln!(f, "{:<10}{}", "Dealer:", self.dealer_hand.join(" "))?;
+        for settlement in &self.settlements {
+            let hand = self
+                .player_hands
+                .get(settlement.hand)
+                .map(|h| h.join(" "))
+                .unwrap_or_default();
+            writeln!(
+                f,
+                "{:<10}hand #{} [{}] bet ${} {}, net ${:.2}",
+                "Result:",
+                settlement.hand + 1,
+                hand,
+                settlement.bet,
+                settlement.result,
+                settlement.net
+            )?;
+        }
+        for side_bet in &self.side_bets {
+            writeln!(f, "{:<10}{}", "Side bet:", side_bet)?;
+        }
+        if let Some(net) = self.insurance {
+            writeln!(f, "{:<10}net ${:.2}", "Insurance:", net)?;
+        }
+        Ok(())
+    }
+}
+
+/// Appends hand histories to a human readable text log and a JSON Lines log
+pub struct HandHistoryWriter {
+    text: File,
+    json: File,
+}
+
+impl HandHistoryWriter {
+    /// Opens (creating them if necessary) the logs `base_path`.txt and `base_path`.jsonl for appending
+    pub fn open<P: AsRef<Path>>(base_path: P) -> std::io::Result<HandHistoryWriter> {
+        let base_path = base_path.as_ref();
+        let open = |extension: &str| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(base_path.with_extension(extension))
+        };
+
+        Ok(HandHistoryWriter {
+            text: open("txt")?,
+            json: open("jsonl")?,
+        })
+    }
+
+    /// Writes `history` to both logs
+    pub fn write(&mut self, history: &HandHistory) -> std::io::Result<()> {
+        writeln!(self.text, "{history}")?;
+        writeln!(self.json, "{}", serde_json::to_string(history)?)
+    }
+}
@@ -1,13 +1,33 @@
+pub mod action;
+pub mod betting;
+pub mod card_style;
+pub mod cli;
+pub mod config;
 pub mod console;
+pub mod history;
+pub mod preset;
+pub mod profile;
+pub mod replay;
+pub mod risk;
+pub mod scenario;
+pub mod session;
+pub mod side_bet;
+pub mod simulator;
+pub mod strategy;
+/// Fixtures shared by the tests that deal stacked shoes to a player, e.g. those of the table, its variants and replays
+#[cfg(test)]
+mod testing;
+pub mod variant;
 
 use rand::prelude::*;
+use rand::rngs::StdRng;
 use std::collections::HashMap;
 use std::fmt::Display;
 use std::rc::Rc;
 use std::str::FromStr;
 
-const SUITS: [&'static str; 4] = ["C", "D", "H", "S"];
-const RANKS: [&'static str; 13] = [
+const SUITS: [&str; 4] = ["C", "D", "H", "S"];
+const RANKS: [&str; 13] = [
     "A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K",
 ];
 
@@ -18,7 +38,7 @@ pub trait BlackjackTable<P: Player> {
     fn play_option(
         &mut self,
         player: &mut P,
-        options: &HashMap<i32, String>,
+        options: &HashMap<i32, action::Action>,
         option: i32,
     ) -> Result<(), BlackjackGameError>;
     fn stand(&self, player: &mut P);
@@ -59,7 +79,7 @@ impl std::error::Error for BlackjackGameError {}
 /// General function for computing the optimal hand at the end of a hand of blackjack.
 /// Takes `hand_value` a vector of u8, and returns its optimal value i.e. the greatest value less than
 /// or equal to 21, if such a value exists in `hand_value`.
-pub fn compute_optimal_hand(hand_value: &Vec<u8>) -> u8 {
+pub fn compute_optimal_hand(hand_value: &[u8]) -> u8 {
     if hand_value.len() == 2 {
         if hand_value[0] > 21 || hand_value[1] > 21 {
             u8::min(hand_value[0], hand_value[1])
@@ -71,6 +91,11 @@ pub fn compute_optimal_hand(hand_value: &Vec<u8>) -> u8 {
     }
 }
 
+/// Parses a whitespace separated sequence of cards such as `"SA HK D10 C6"`, see `Card::from_str`
+pub fn parse_cards(sequence: &str) -> Result<Vec<Card>, BlackjackGameError> {
+    sequence.split_whitespace().map(Card::from_str).collect()
+}
+
 /// A struct for representing a single playing card. Comprised of two fields, `suit` and `rank`.
 #[derive(PartialEq, Eq, Debug)]
 pub struct Card {
@@ -108,13 +133,140 @@ impl Display for Card {
     }
 }
 
+impl FromStr for Card {
+    type Err = BlackjackGameError;
+
+    /// Parses a card from the same format it is displayed in, i.e. the suit followed by the rank such as `HQ` or `D10`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid_card = || BlackjackGameError::new(format!("{s} is not a valid card"));
+        if !s.is_ascii() || s.len() < 2 {
+            return Err(invalid_card());
+        }
+
+        let (suit, rank) = s.split_at(1);
+        let suit = SUITS
+            .iter()
+            .copied()
+            .find(|x| x.eq_ignore_ascii_case(suit))
+            .ok_or_else(invalid_card)?;
+        let rank = RANKS
+            .iter()
+            .copied()
+            .find(|x| x.eq_ignore_ascii_case(rank))
+            .ok_or_else(invalid_card)?;
+        Ok(Card::new(suit, rank))
+    }
+}
+
+/// Where the cut card is placed in the shoe after shuffling, given as fractions of the shoe that are dealt before reshuffling
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CutCard {
+    /// The cut card is always placed at the same fraction of the shoe
+    Fixed(f32),
+    /// The cut card is placed uniformly at random between the two fractions of the shoe
+    Random(f32, f32),
+}
+
+impl CutCard {
+    /// Checks that the cut card is placed within the shoe, i.e. every fraction lies in (0, 1] and a range is not reversed
+    pub fn validate(&self) -> Result<(), BlackjackGameError> {
+        let in_shoe = |f: f32| f > 0.0 && f <= 1.0;
+        match *self {
+            CutCard::Fixed(f) if in_shoe(f) => Ok(()),
+            CutCard::Random(low, high) if in_shoe(low) && in_shoe(high) && low <= high => Ok(()),
+            _ => Err(BlackjackGameError::new(format!(
+                "{self:?} is not a valid cut card placement, penetration must be between 0 and 1 with the lower bound first"
+            ))),
+        }
+    }
+
+    /// Returns the position of the cut card in a shoe of `n_cards` cards
+    fn position<R: Rng>(&self, n_cards: usize, rng: &mut R) -> usize {
+        let penetration = match *self {
+            CutCard::Fixed(f) => f,
+            CutCard::Random(low, high) if low < high => rng.gen_range(low..=high),
+            CutCard::Random(low, _) => low,
+        };
+        f32::floor(((n_cards - 1) as f32) * penetration) as usize
+    }
+}
+
+/// How the cards of a shoe are brought back into play once they have been dealt
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShoeMode {
+    /// Cards are dealt until the cut card is reached, and then the whole shoe is shuffled
+    HandShuffled,
+    /// A continuous shuffling machine, the cards of every finished round are returned to a random position in the shoe.
+    /// Returned cards are never placed among the next `buffer` cards, which the machine has already made ready to deal
+    Continuous { buffer: usize },
+}
+
+/// The number of cards of each rank in a collection of cards, such as the cards remaining in a shoe
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Composition {
+    counts: [usize; 13],
+}
+
+impl Composition {
+    /// Counts the cards of each rank in `cards`
+    pub fn from_cards(cards: &[Rc<Card>]) -> Composition {
+        let mut composition = Composition::default();
+        for card in cards {
+            let idx = RANKS.iter().position(|r| *r == card.rank).unwrap();
+            composition.counts[idx] += 1;
+        }
+        composition
+    }
+
+    /// Returns the number of cards of rank `rank`, i.e. `"A"`, `"10"` or `"K"`
+    pub fn count(&self, rank: &str) -> usize {
+        RANKS
+            .iter()
+            .position(|r| *r == rank)
+            .map_or(0, |idx| self.counts[idx])
+    }
+
+    /// Returns the number of cards with value `val`, aces have the value 1 and every ten and face card has the value 10
+    pub fn count_value(&self, val: u8) -> usize {
+        RANKS
+            .iter()
+            .zip(self.counts)
+            .filter(|(rank, _)| Card::get_card_value(rank) == val)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// Returns the number of ten valued cards i.e. tens, jacks, queens and kings
+    pub fn tens(&self) -> usize {
+        self.count_value(10)
+    }
+
+    /// Returns the total number of cards
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
 /// A simple struct that acts as a collection of playing cards of type Card.
+/// The cards before `discard_pos` are in the discard tray, the cards between `discard_pos` and `deck_pos` are
+/// in play in the current round, and the cards from `deck_pos` onwards are still in the shoe.
 pub struct Deck {
     cards: Vec<Rc<Card>>,
     n_decks: usize,
+    /// The number of cards in each deck of the shoe, 48 for Spanish 21 and 52 otherwise
+    cards_per_deck: usize,
     deck_pos: usize,
+    discard_pos: usize,
     shuffle_flag_pos: usize,
     pub shuffle_flag: bool,
+    shoe_id: u32,
+    cut_card: CutCard,
+    burn_cards: usize,
+    /// The number of burned cards at the start of the discard tray, which were never seen
+    burned: usize,
+    last_penetration: Option<f32>,
+    shoe_mode: ShoeMode,
+    rng: StdRng,
 }
 
 /// A struct to represent a deck of cards, is basically a collection of card structs that implements some specific logic related to a game of blackjack
@@ -135,39 +287,260 @@ impl Deck {
     /// Creates and returns a new Deck struct
     pub fn new(n_decks: usize) -> Deck {
         assert!(n_decks > 0, "Cannot have a deck with zero cards");
-        let cards = Self::build_card_deck(n_decks);
-        let n_cards = cards.len();
-        let shuffle_flag_pos = f32::floor(((n_cards - 1) as f32) * 0.8) as usize;
+        let mut deck = Self::stacked(Self::build_card_deck(n_decks), n_decks);
+        deck.shuffle_flag = true;
+        deck
+    }
+
+    /// Creates a new Deck struct for Spanish 21, i.e. every deck is missing its four ten pip cards
+    pub fn new_spanish(n_decks: usize) -> Deck {
+        assert!(n_decks > 0, "Cannot have a deck with zero cards");
+        let mut cards = Self::build_card_deck(n_decks);
+        cards.retain(|c| c.rank != "10");
+        let mut deck = Self::stacked(cards, n_decks);
+        deck.cards_per_deck = 48;
+        deck.shuffle_flag = true;
+        deck
+    }
+
+    /// Creates a deck that deals exactly `cards`, in order. The deck is not flagged for shuffling, so the
+    /// cards are dealt in the given order until the deck is exhausted
+    pub fn from_cards(cards: Vec<Card>) -> Deck {
+        assert!(!cards.is_empty(), "Cannot have a deck with zero cards");
+        let cards: Vec<Rc<Card>> = cards.into_iter().map(Rc::new).collect();
+        let n_decks = usize::max(1, cards.len().div_ceil(52));
+        Self::stacked(cards, n_decks)
+    }
+
+    /// Creates a deck from a whitespace separated sequence of cards such as `"SA HK D10 C6"`, dealt in the given order.
+    /// Cards are dealt to the player then the dealer, twice, and the dealers first card is the hole card, i.e. the
+    /// sequence `"H10 SA D7 SK"` deals the player H10 D7 and the dealer a blackjack with SK showing
+    pub fn from_card_sequence(sequence: &str) -> Result<Deck, BlackjackGameError> {
+        let cards = parse_cards(sequence)?;
+        if cards.is_empty() {
+            return Err(BlackjackGameError::new(String::from(
+                "Cannot have a deck with zero cards",
+            )));
+        }
+        Ok(Self::from_cards(cards))
+    }
+
+    /// Creates a shoe of `n_decks` decks that first deals `prefix` in order, followed by the rest of the shoe in a random order.
+    /// The cards of `prefix` are taken out of the shoe, so the shoe has the same composition as a regular shoe. The rest
+    /// of the shoe is shuffled from `seed` if there is one, so the same seed always deals the same shoe. Returns an error
+    /// if `prefix` uses a card more times than the shoe contains it
+    pub fn with_prefix(
+        prefix: Vec<Card>,
+        n_decks: usize,
+        seed: Option<u64>,
+    ) -> Result<Deck, BlackjackGameError> {
+        assert!(n_decks > 0, "Cannot have a deck with zero cards");
+        let mut rest = Self::build_card_deck(n_decks);
+        for card in &prefix {
+            let Some(idx) = rest.iter().position(|c| **c == *card) else {
+                return Err(BlackjackGameError::new(format!(
+                    "A shoe of {n_decks} decks does not have enough {card} cards to deal {}",
+                    prefix
+                        .iter()
+                        .map(|c| c.to_string())
+                        .collect::<Vec<String>>()
+                        .join(" ")
+                )));
+            };
+            rest.swap_remove(idx);
+        }
+
+        let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        rest.shuffle(&mut rng);
+        let mut cards: Vec<Rc<Card>> = prefix.into_iter().map(Rc::new).collect();
+        cards.extend(rest);
+        let mut deck = Self::stacked(cards, n_decks);
+        deck.rng = rng;
+        Ok(deck)
+    }
+
+    /// Creates a deck that deals `cards` in order, without shuffling first
+    fn stacked(cards: Vec<Rc<Card>>, n_decks: usize) -> Deck {
+        let cut_card = CutCard::Fixed(0.8);
+        let shuffle_flag_pos = cut_card.position(cards.len(), &mut rand::thread_rng());
 
         Deck {
             cards,
             n_decks,
+            cards_per_deck: 52,
             deck_pos: 0,
+            discard_pos: 0,
             shuffle_flag_pos,
-            shuffle_flag: true,
+            shuffle_flag: false,
+            shoe_id: 0,
+            cut_card,
+            burn_cards: 0,
+            burned: 0,
+            last_penetration: None,
+            shoe_mode: ShoeMode::HandShuffled,
+            rng: StdRng::from_entropy(),
         }
     }
 
+    /// Seeds the random number generator the deck is shuffled with, so that the same seed always shuffles the same shoes
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Sets where the cut card is placed the next time the deck is shuffled, the default is a fixed penetration of 80%.
+    /// The method panics if `cut_card` is not a valid placement, see `CutCard::validate`
+    pub fn set_cut_card(&mut self, cut_card: CutCard) {
+        assert!(cut_card.validate().is_ok(), "invalid cut card placement");
+        self.cut_card = cut_card;
+    }
+
+    /// Sets the number of cards burned, i.e. discarded unseen, from the top of the shoe after every shuffle
+    pub fn set_burn_cards(&mut self, burn_cards: usize) {
+        assert!(
+            burn_cards < self.cards.len(),
+            "Cannot burn every card in the shoe"
+        );
+        self.burn_cards = burn_cards;
+    }
+
+    /// Sets how dealt cards are brought back into play, the default is a hand shuffled shoe.
+    /// The method panics if the buffer of a continuous shuffling machine is not smaller than the shoe
+    pub fn set_shoe_mode(&mut self, shoe_mode: ShoeMode) {
+        if let ShoeMode::Continuous { buffer } = shoe_mode {
+            assert!(
+                buffer < self.cards.len(),
+                "The machine buffer must be smaller than the shoe"
+            );
+        }
+        self.shoe_mode = shoe_mode;
+    }
+
+    /// Returns how dealt cards are brought back into play
+    pub fn shoe_mode(&self) -> ShoeMode {
+        self.shoe_mode
+    }
+
+    /// Signals the end of a round. With a continuous shuffling machine every card dealt in the round is returned to a
+    /// random position in the shoe behind the machines buffer, with a hand shuffled shoe the cards are moved to the
+    /// discard tray until the shoe is reshuffled
+    pub fn finish_round(&mut self) {
+        match self.shoe_mode {
+            ShoeMode::HandShuffled => self.discard_pos = self.deck_pos,
+            ShoeMode::Continuous { buffer } => {
+                let returned_cards: Vec<Rc<Card>> = self.cards.drain(..self.deck_pos).collect();
+                self.deck_pos = 0;
+                self.discard_pos = 0;
+                self.burned = 0;
+                for card in returned_cards {
+                    let min_idx = usize::min(buffer, self.cards.len());
+                    let idx = self.rng.gen_range(min_idx..=self.cards.len());
+                    self.cards.insert(idx, card);
+                }
+            }
+        }
+    }
+
+    /// Returns the cards in the discard tray, i.e. burned cards and the cards of every finished round since the last shuffle
+    pub fn discard_tray(&self) -> &[Rc<Card>] {
+        &self.cards[..self.discard_pos]
+    }
+
+    /// Returns the cards in the discard tray that were dealt face up, i.e. the discard tray without the burned cards
+    pub fn seen_discards(&self) -> &[Rc<Card>] {
+        &self.cards[self.burned..self.discard_pos]
+    }
+
+    /// Returns the cards that have been dealt in the current round, in the order they were dealt
+    pub fn cards_in_play(&self) -> &[Rc<Card>] {
+        &self.cards[self.discard_pos..self.deck_pos]
+    }
+
+    /// Returns the cards still in the shoe, in the order they will be dealt
+    pub fn remaining_cards(&self) -> &[Rc<Card>] {
+        &self.cards[self.deck_pos..]
+    }
+
+    /// Returns the number of cards of each rank still in the shoe, i.e. `remaining_composition().tens()` is the
+    /// number of ten valued cards left to be dealt
+    pub fn remaining_composition(&self) -> Composition {
+        Composition::from_cards(self.remaining_cards())
+    }
+
+    /// Returns the number of cards of each rank in the discard tray
+    pub fn discard_composition(&self) -> Composition {
+        Composition::from_cards(self.discard_tray())
+    }
+
+    /// Shuffles the cards in the discard tray back into the shoe, behind the cards that are still in the shoe.
+    /// The cards in play stay out of the shoe.
+    fn reshuffle_discards(&mut self) {
+        let mut discards: Vec<Rc<Card>> = self.cards.drain(..self.discard_pos).collect();
+        discards.shuffle(&mut self.rng);
+        self.deck_pos -= self.discard_pos;
+        self.discard_pos = 0;
+        self.burned = 0;
+        self.cards.extend(discards);
+    }
+
+    /// Returns the fraction of the current shoe that has been dealt so far, including burned cards
+    pub fn penetration(&self) -> f32 {
+        self.deck_pos as f32 / self.cards.len() as f32
+    }
+
+    /// Returns the penetration that was reached in the previous shoe before it was reshuffled,
+    /// or None if the deck has not been reshuffled after dealing from it yet
+    pub fn last_penetration(&self) -> Option<f32> {
+        self.last_penetration
+    }
+
     /// Shuffles the deck of cards to simulate the random behavior of a shuffled deck of cards
     pub fn shuffle(&mut self, n_shuffles: u32) {
         assert!(n_shuffles > 0);
-        let mut rng = rand::thread_rng();
         for _ in 0..n_shuffles {
             for i in 0..(self.cards.len() / 2) {
-                let random_idx = rng.gen_range(0..self.cards.len());
+                let random_idx = self.rng.gen_range(0..self.cards.len());
                 self.cards.swap(i, random_idx);
             }
         }
-        self.deck_pos = 0;
+        if self.deck_pos > 0 {
+            self.last_penetration = Some(self.penetration());
+        }
+
+        // Place the cut card and burn the top cards of the newly shuffled shoe
+        self.shuffle_flag_pos = self.cut_card.position(self.cards.len(), &mut self.rng);
+        self.deck_pos = self.burn_cards;
+        self.discard_pos = self.burn_cards;
+        self.burned = self.burn_cards;
         self.shuffle_flag = false;
+        self.shoe_id += 1;
+    }
+
+    /// Returns the number of standard 52 card decks that make up the shoe
+    pub fn n_decks(&self) -> usize {
+        self.n_decks
+    }
+
+    /// Returns the number of cards in each deck of the shoe, i.e. 48 for the decks of Spanish 21 without their tens
+    pub fn cards_per_deck(&self) -> usize {
+        self.cards_per_deck
+    }
+
+    /// Returns the id of the current shoe, the id is incremented every time the deck is shuffled
+    pub fn shoe_id(&self) -> u32 {
+        self.shoe_id
     }
 
     /// Returns the next card, i.e. the card that is at the top of the deck of cards
+    /// Should the shoe run out in the middle of a round, the discard tray is shuffled back into the shoe first.
     pub fn get_next_card(&mut self) -> Option<Rc<Card>> {
+        if self.deck_pos == self.cards.len() && self.discard_pos > 0 {
+            self.reshuffle_discards();
+        }
+
         if self.deck_pos < self.cards.len() {
             let next_card = Some(Rc::clone(&self.cards[self.deck_pos]));
             self.deck_pos += 1;
-            if self.deck_pos == self.shuffle_flag_pos {
+            if self.shoe_mode == ShoeMode::HandShuffled && self.deck_pos >= self.shuffle_flag_pos {
                 self.shuffle_flag = true;
             }
             return next_card;
@@ -640,15 +1013,269 @@ impl Deck {
 //     }
 // }
 
+use crate::betting::BetSizing;
+use crate::config::{GameConfig, UiMode};
 pub use crate::console::{
-    player::ConsolePlayer, table::ConsoleBlackjackTable, ConsoleBlackjackGame,
+    player::ConsolePlayer, table::ConsoleBlackjackTable, tui::TuiBlackjackGame,
+    ConsoleBlackjackGame,
 };
+use crate::history::{HandHistoryWriter, DEFAULT_HISTORY_PATH};
+use crate::profile::{PlayerProfile, ProfileStore};
+use crate::simulator::SimulationReport;
+use crate::variant::GameVariant;
+
+/// Prompts the player for their name via the console, and returns the trimmed name
+fn get_players_name() -> std::io::Result<String> {
+    loop {
+        println!("Please enter your name: ");
+        let mut name = String::new();
+        std::io::stdin().read_line(&mut name)?;
+        let name = name.trim();
+        if !name.is_empty() {
+            return Ok(name.to_string());
+        }
+        println!("Name cannot be empty");
+    }
+}
+
+/// Loads the profile of the player named in `config` from `profiles`, asking for their name if `config` does not give
+/// one, or creates a new profile for a new player
+fn get_player(profiles: &ProfileStore, config: &GameConfig) -> std::io::Result<ConsolePlayer> {
+    let name = match &config.name {
+        Some(name) => name.clone(),
+        None => get_players_name()?,
+    };
+    let mut profile = match profiles.get(&name) {
+        Some(profile) => {
+            println!("Welcome back, {}", profile.name);
+            profile.stats.display();
+            profile.clone()
+        }
+        None => {
+            println!("Welcome, {name}");
+            PlayerProfile::new(name, config.starting_balance)
+        }
+    };
+
+    // A player who can no longer cover the minimum bet would be stuck, so restake them
+    if profile.balance < config.minimum_bet as f32 {
+        println!(
+            "Your balance has been reset to ${}",
+            config.starting_balance
+        );
+        profile.balance = config.starting_balance;
+    }
+
+    Ok(ConsolePlayer::from_profile(profile))
+}
+
+/// Returns a table dealing from `deck` set up by `config`
+fn table_from_config(config: &GameConfig, mut deck: Deck) -> ConsoleBlackjackTable {
+    if let Some(seed) = config.seed {
+        deck.set_seed(seed);
+    }
+    deck.set_cut_card(config.cut_card);
+    deck.set_burn_cards(config.burn_cards);
+    deck.set_shoe_mode(config.shoe_mode);
+    let mut table = ConsoleBlackjackTable::with_deck(
+        config.table_balance,
+        deck,
+        config.n_shuffles,
+        config.variant,
+    );
+    table.set_hole_card_rule(config.hole_card);
+    table.set_house_rules(config.rules);
+    table.set_bet_limits(config.minimum_bet, config.maximum_bet);
+    table.set_card_style(config.card_style);
+    for (side_bet, paytable) in &config.side_bets {
+        table.offer_side_bet(*side_bet, paytable.clone());
+    }
+    table
+}
 
-pub fn run() -> std::io::Result<()> {
-    let player = ConsolePlayer::new(String::from("Rick Sanchez"), 500.0);
-    let table = ConsoleBlackjackTable::new(500000000.0, 6, 7);
-    let mut game = ConsoleBlackjackGame::new(5, player, table);
+/// Plays a game of blackjack set up by `config`, with the front-end it chooses
+pub fn run(config: &GameConfig) -> std::io::Result<()> {
+    let profiles = ProfileStore::load(&config.profile_path)?;
+    let player = get_player(&profiles, config)?;
+    let table = table_from_config(config, config.variant.build_deck(config.n_decks));
+    let history = HandHistoryWriter::open(DEFAULT_HISTORY_PATH)?;
+    match config.ui {
+        UiMode::Console => ConsoleBlackjackGame::new(
+            config.minimum_bet,
+            player,
+            table,
+            Some(profiles),
+            Some(history),
+        )
+        .play(),
+        UiMode::Tui => TuiBlackjackGame::new(
+            config.minimum_bet,
+            player,
+            table,
+            Some(profiles),
+            Some(history),
+        )
+        .play(),
+    }
+}
+
+/// Plays a game whose first round is dealt from the stacked scenario named `name`, at a standard table otherwise set up
+/// by `config`. If there is no such scenario the available scenarios are listed instead
+pub fn run_scenario(name: &str, config: &GameConfig) -> std::io::Result<()> {
+    let Some(scenario) = scenario::Scenario::find(name) else {
+        println!("Unknown scenario '{name}', available scenarios:");
+        for scenario in &scenario::SCENARIOS {
+            println!("\t{:<20}{}", scenario.name, scenario.description);
+        }
+        return Ok(());
+    };
+
+    let deck = scenario
+        .deck(config.n_decks, config.seed)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    println!("{}", scenario.description);
+    let name = config.name.clone().unwrap_or(String::from("Player"));
+    let player = ConsolePlayer::new(name, config.starting_balance);
+    let config = GameConfig {
+        variant: GameVariant::Standard,
+        ..config.clone()
+    };
+    let table = table_from_config(&config, deck);
+    let mut game = ConsoleBlackjackGame::new(config.minimum_bet, player, table, None, None);
     game.play()?;
 
     Ok(())
 }
+
+/// Replays every round in the JSON Lines hand history log at `path`, and reports any round where the engine
+/// no longer reaches the recorded result
+pub fn run_replay(path: &str) -> std::io::Result<()> {
+    let report = replay::replay_file(path)?;
+    report.display();
+
+    Ok(())
+}
+
+/// Simulates `rounds` rounds with a bot playing each of `sizings` at a table set up by `config`, and reports how each
+/// betting scheme fared. Every scheme is dealt the same shuffles, from the configs seed or a random seed if it has none
+pub fn run_simulation(
+    config: &GameConfig,
+    sizings: &[BetSizing],
+    rounds: u32,
+) -> std::io::Result<()> {
+    let config = GameConfig {
+        seed: Some(config.seed.unwrap_or_else(rand::random)),
+        ..config.clone()
+    };
+    let maximum_bet = config
+        .maximum_bet
+        .map_or(String::from("no maximum"), |max| format!("${max}"));
+    println!(
+        "Simulating {rounds} rounds of {} ({}) per betting scheme, bets ${} to {maximum_bet}, starting balance ${:.2}",
+        config.variant, config.rules, config.minimum_bet, config.starting_balance
+    );
+    println!();
+
+    let mut reports: Vec<SimulationReport> = vec![];
+    for sizing in sizings {
+        let report =
+            simulator::simulate(&config, sizing.clone(), rounds).map_err(std::io::Error::other)?;
+        reports.push(report);
+    }
+    SimulationReport::display_all(&reports, config.card_style.unicode);
+
+    Ok(())
+}
+
+/// Computes the risk of ruin of the starting balance of `config` for a bot playing each of `sizings` at a table set up by
+/// `config`, both analytically and over `trials` simulated sessions of up to `rounds` rounds, along with the bankroll
+/// needed for a risk of ruin of `target`
+pub fn run_risk(
+    config: &GameConfig,
+    sizings: &[BetSizing],
+    trials: u32,
+    rounds: u32,
+    target: f32,
+) -> std::io::Result<()> {
+    println!(
+        "Simulating {trials} sessions of up to {rounds} rounds of {} ({}) per betting scheme, bets ${} and up",
+        config.variant, config.rules, config.minimum_bet
+    );
+    for sizing in sizings {
+        let report = risk::simulate_risk_of_ruin(
+            config,
+            sizing,
+            config.starting_balance,
+            trials,
+            rounds,
+            target,
+        )
+        .map_err(std::io::Error::other)?;
+        report.display();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_prefix_rejects_cards_missing_from_the_shoe() {
+        let prefix = || parse_cards("SA SA SK").unwrap();
+        assert!(Deck::with_prefix(prefix(), 1, None).is_err());
+        assert!(Deck::with_prefix(prefix(), 2, None).is_ok());
+    }
+
+    #[test]
+    fn with_prefix_shuffles_the_rest_from_the_seed() {
+        let shoe = |seed| {
+            let deck = Deck::with_prefix(parse_cards("SA HK").unwrap(), 2, Some(seed)).unwrap();
+            deck.remaining_cards()
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(shoe(3), shoe(3));
+        assert_ne!(shoe(3), shoe(4));
+        assert_eq!(shoe(3)[..2], ["SA", "HK"]);
+    }
+
+    #[test]
+    fn cards_in_play_survive_a_reshuffle_in_the_middle_of_a_round() {
+        let names = |cards: &[Rc<Card>]| cards.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let mut deck = Deck::from_card_sequence("SA SK SQ SJ").unwrap();
+        deck.get_next_card();
+        deck.get_next_card();
+        deck.finish_round();
+        assert_eq!(names(deck.discard_tray()), ["SA", "SK"]);
+
+        // The shoe runs out after two cards, so the discard tray is shuffled back in to deal the third
+        let dealt: Vec<String> = (0..3)
+            .map(|_| deck.get_next_card().unwrap().to_string())
+            .collect();
+        assert_eq!(names(deck.cards_in_play()), dealt);
+        assert_eq!(dealt[..2], ["SQ", "SJ"]);
+        assert!(deck.discard_tray().is_empty());
+    }
+
+    #[test]
+    fn continuous_shuffling_returns_the_cards_in_play() {
+        let mut deck = Deck::new(1);
+        deck.set_shoe_mode(ShoeMode::Continuous { buffer: 10 });
+        let dealt: Vec<String> = (0..5)
+            .map(|_| deck.get_next_card().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            deck.cards_in_play()
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>(),
+            dealt
+        );
+        deck.finish_round();
+        assert!(deck.cards_in_play().is_empty());
+        assert_eq!(deck.remaining_cards().len(), 52);
+    }
+}
@@ -1,7 +1,42 @@
-use blackjack_lib::run;
+use blackjack_lib::betting::{BettingSystem, BETTING_SYSTEMS};
+use blackjack_lib::cli::{Cli, Command};
+use blackjack_lib::{run, run_replay, run_risk, run_scenario, run_simulation};
+use clap::Parser;
 use std::error::Error;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    run()?;
+    let cli = Cli::parse();
+    // Replaying a log does not use the game settings, so a bad config file only stops the commands that do
+    let config = || cli.config().unwrap_or_else(|e| e.exit());
+    match &cli.command {
+        Some(Command::Replay { path }) => run_replay(path)?,
+        Some(Command::Scenario { scenario }) => {
+            run_scenario(scenario.as_deref().unwrap_or_default(), &config())?
+        }
+        Some(Command::Simulate { rounds, betting }) => {
+            let config = config();
+            run_simulation(
+                &config,
+                &betting.bet_sizings(config.minimum_bet, &BETTING_SYSTEMS),
+                *rounds,
+            )?
+        }
+        Some(Command::Risk {
+            target,
+            trials,
+            rounds,
+            betting,
+        }) => {
+            let config = config();
+            run_risk(
+                &config,
+                &betting.bet_sizings(config.minimum_bet, &[BettingSystem::Flat]),
+                *trials,
+                *rounds,
+                *target / 100.0,
+            )?
+        }
+        None => run(&config())?,
+    }
     Ok(())
 }
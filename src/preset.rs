@@ -0,0 +1,93 @@
+use crate::config::GameConfig;
+use crate::variant::{BlackjackPayout, GameVariant, HoleCardRule, HouseRules};
+use crate::CutCard;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::fmt::Display;
+
+/// A named table definition, setting the variant, house rules and shoe a table is dealt with.
+/// The variant presets deal their variant from a six deck shoe, the casino presets follow the rules typical of the casinos
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    /// Standard blackjack, the dealer peeks for blackjack
+    #[default]
+    Standard,
+    /// Spanish 21, played without the ten pip cards
+    Spanish21,
+    /// Blackjack Switch, two hands with their second cards switchable
+    Switch,
+    /// Double Exposure, both of the dealers cards face up
+    DoubleExposure,
+    /// Free Bet Blackjack, free doubles and splits funded by the house
+    FreeBet,
+    /// Pontoon, both of the dealers cards face down
+    Pontoon,
+    /// Six decks, the dealer stands on soft 17 and late surrender is offered
+    VegasStrip,
+    /// Eight decks, the dealer stands on soft 17 and late surrender is offered
+    AtlanticCity,
+    /// Two decks, the dealer hits soft 17
+    DowntownVegas,
+    /// Six decks dealt European style without a hole card, the dealer stands on soft 17
+    European,
+    /// A single deck where the dealer hits soft 17 and blackjack only pays 6:5
+    #[value(name = "6-5-single-deck")]
+    #[serde(rename = "6-5-single-deck")]
+    SixFiveSingleDeck,
+}
+
+impl Preset {
+    /// Sets the variant, house rules and shoe of `config` to those of the preset, leaving its other settings alone
+    pub fn apply(&self, config: &mut GameConfig) {
+        let (variant, hole_card) = match self {
+            Preset::Spanish21 => (GameVariant::Spanish21, HoleCardRule::Peek),
+            Preset::Switch => (GameVariant::Switch, HoleCardRule::Peek),
+            Preset::DoubleExposure => (GameVariant::DoubleExposure, HoleCardRule::Peek),
+            Preset::FreeBet => (GameVariant::FreeBet, HoleCardRule::Peek),
+            Preset::Pontoon => (GameVariant::Pontoon, HoleCardRule::Peek),
+            Preset::European => (GameVariant::Standard, HoleCardRule::NoHoleCard),
+            _ => (GameVariant::Standard, HoleCardRule::Peek),
+        };
+        let (n_decks, penetration) = match self {
+            Preset::VegasStrip => (6, 0.75),
+            Preset::AtlanticCity => (8, 0.75),
+            Preset::DowntownVegas => (2, 0.7),
+            Preset::SixFiveSingleDeck => (1, 0.6),
+            _ => (6, 0.8),
+        };
+        let rules = HouseRules {
+            dealer_hits_soft_17: matches!(self, Preset::DowntownVegas | Preset::SixFiveSingleDeck),
+            blackjack_pays: match self {
+                Preset::SixFiveSingleDeck => BlackjackPayout::SixToFive,
+                _ => BlackjackPayout::ThreeToTwo,
+            },
+            late_surrender: matches!(self, Preset::VegasStrip | Preset::AtlanticCity),
+        };
+
+        config.variant = variant;
+        config.hole_card = hole_card;
+        config.rules = rules;
+        config.n_decks = n_decks;
+        config.cut_card = CutCard::Fixed(penetration);
+    }
+}
+
+impl Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Preset::Standard => "Standard",
+            Preset::Spanish21 => "Spanish 21",
+            Preset::Switch => "Blackjack Switch",
+            Preset::DoubleExposure => "Double Exposure",
+            Preset::FreeBet => "Free Bet",
+            Preset::Pontoon => "Pontoon",
+            Preset::VegasStrip => "Vegas Strip",
+            Preset::AtlanticCity => "Atlantic City",
+            Preset::DowntownVegas => "Downtown Vegas",
+            Preset::European => "European",
+            Preset::SixFiveSingleDeck => "6:5 Single Deck",
+        };
+        write!(f, "{name}")
+    }
+}
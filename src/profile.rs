@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// The default file used to store player profiles between sessions
+pub const DEFAULT_PROFILE_PATH: &str = "profiles.json";
+
+/// Lifetime statistics of a player, accumulated over every session the player has played
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PlayerStats {
+    /// Number of rounds played, a split hand counts as a single round
+    pub hands_played: u32,
+    /// Number of individual hands won, each hand of a split is counted separately
+    pub wins: u32,
+    /// Number of individual hands lost, each hand of a split is counted separately
+    pub losses: u32,
+    /// Number of individual hands pushed, each hand of a split is counted separately
+    pub pushes: u32,
+    pub blackjacks: u32,
+    /// The largest net amount won in a single round
+    pub biggest_win: f32,
+    /// The net amount won (or lost if negative) over all rounds played
+    pub net_result: f32,
+    /// Number of playing decisions made
+    pub decisions: u32,
+    /// Number of playing decisions that agreed with basic strategy
+    pub correct_decisions: u32,
+    /// Statistics of every side bet the player has placed, keyed by the name of the side bet
+    #[serde(default)]
+    pub side_bets: BTreeMap<String, SideBetStats>,
+}
+
+/// Lifetime statistics of a single kind of side bet
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SideBetStats {
+    pub placed: u32,
+    pub won: u32,
+    /// The net amount won (or lost if negative) over every side bet of this kind placed
+    pub net_result: f32,
+}
+
+impl PlayerStats {
+    /// Records a single hand that was won
+    pub fn record_win(&mut self) {
+        self.wins += 1;
+    }
+
+    /// Records a single hand that was lost
+    pub fn record_loss(&mut self) {
+        self.losses += 1;
+    }
+
+    /// Records a single hand that was pushed
+    pub fn record_push(&mut self) {
+        self.pushes += 1;
+    }
+
+    /// Records that the player was dealt a blackjack
+    pub fn record_blackjack(&mut self) {
+        self.blackjacks += 1;
+    }
+
+    /// Records a playing decision, `correct` should be true if the decision agreed with basic strategy
+    pub fn record_decision(&mut self, correct: bool) {
+        self.decisions += 1;
+        if correct {
+            self.correct_decisions += 1;
+        }
+    }
+
+    /// Records a settled side bet named `side_bet`, where `net` is the amount won or lost if negative
+    pub fn record_side_bet(&mut self, side_bet: &str, net: f32) {
+        let stats = self.side_bets.entry(side_bet.to_string()).or_default();
+        stats.placed += 1;
+        if net > 0.0 {
+            stats.won += 1;
+        }
+        stats.net_result += net;
+    }
+
+    /// Records the end of a round, where `net` is the net amount won or lost over the round
+    pub fn record_round(&mut self, net: f32) {
+        self.hands_played += 1;
+        self.net_result += net;
+        if net > self.biggest_win {
+            self.biggest_win = net;
+        }
+    }
+
+    /// Returns the percentage of decisions that agreed with basic strategy, or None if no decisions have been made
+    pub fn strategy_accuracy(&self) -> Option<f32> {
+        match self.decisions {
+            0 => None,
+            n => Some(100.0 * (self.correct_decisions as f32) / (n as f32)),
+        }
+    }
+
+    /// Displays the statistics to the console
+    pub fn display(&self) {
+        let accuracy = match self.strategy_accuracy() {
+            Some(a) => format!("{a:.1}%"),
+            None => String::from("n/a"),
+        };
+        println!("{:<20}{}", "Hands played:", self.hands_played);
+        println!(
+            "{:<20}{}/{}/{}",
+            "Won/Lost/Pushed:", self.wins, self.losses, self.pushes
+        );
+        println!("{:<20}{}", "Blackjacks:", self.blackjacks);
+        println!("{:<20}${:.2}", "Biggest win:", self.biggest_win);
+        println!("{:<20}${:.2}", "Net result:", self.net_result);
+        println!("{:<20}{}", "Strategy accuracy:", accuracy);
+        for (side_bet, stats) in &self.side_bets {
+            println!(
+                "{:<20}{}/{} won, net ${:.2}",
+                format!("{side_bet}:"),
+                stats.won,
+                stats.placed,
+                stats.net_result
+            );
+        }
+    }
+}
+
+/// A saved player, i.e. a players name along with their bankroll and lifetime statistics
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlayerProfile {
+    pub name: String,
+    pub balance: f32,
+    pub stats: PlayerStats,
+}
+
+impl PlayerProfile {
+    /// Creates a new profile with a starting balance of `balance` and no statistics
+    pub fn new(name: String, balance: f32) -> PlayerProfile {
+        PlayerProfile {
+            name,
+            balance,
+            stats: PlayerStats::default(),
+        }
+    }
+}
+
+/// A local store of player profiles keyed by player name, persisted as a JSON file
+pub struct ProfileStore {
+    path: PathBuf,
+    profiles: HashMap<String, PlayerProfile>,
+}
+
+impl ProfileStore {
+    /// Loads the profiles stored in the file at `path`. If the file does not exist yet an empty store is returned,
+    /// and the file will be created the first time the store is saved
+    pub fn load<P: AsRef<Path>>(path: P) -> std::io::Result<ProfileStore> {
+        let path = path.as_ref().to_path_buf();
+        let profiles = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(ProfileStore { path, profiles })
+    }
+
+    /// Returns the profile of the player named `name` if one exists
+    pub fn get(&self, name: &str) -> Option<&PlayerProfile> {
+        self.profiles.get(name)
+    }
+
+    /// Inserts `profile` into the store, replacing any existing profile with the same name
+    pub fn update(&mut self, profile: PlayerProfile) {
+        self.profiles.insert(profile.name.clone(), profile);
+    }
+
+    /// Writes every profile in the store to its file
+    pub fn save(&self) -> std::io::Result<()> {
+        let contents = serde_json::to_string_pretty(&self.profiles)?;
+        std::fs::write(&self.path, contents)
+    }
+}
@@ -0,0 +1,267 @@
+use crate::action::Action;
+use crate::console::player::ConsolePlayer;
+use crate::console::table::ConsoleBlackjackTable;
+use crate::history::HandHistory;
+use crate::{BlackjackGameError, BlackjackTable, Card, Deck, RANKS, SUITS};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Balance given to the replayed player and table, large enough that every recorded bet, double and split can be covered
+const REPLAY_BALANCE: f32 = 1_000_000_000.0;
+
+/// A difference between a recorded round and the same round replayed by the engine
+#[derive(Debug)]
+pub struct Divergence {
+    pub round: u32,
+    pub message: String,
+}
+
+/// The result of replaying every round of a hand history log
+#[derive(Debug)]
+pub struct ReplayReport {
+    pub rounds: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+impl ReplayReport {
+    /// Displays a summary of the replay, listing every divergence found, to the console
+    pub fn display(&self) {
+        println!("{}", "-".repeat(80));
+        println!(
+            "Replayed {} round(s), {} divergence(s)",
+            self.rounds,
+            self.divergences.len()
+        );
+        for divergence in &self.divergences {
+            println!("Round #{}: {}", divergence.round, divergence.message);
+        }
+    }
+}
+
+/// Reads every hand history recorded in the JSON Lines log at `path`
+pub fn read_hand_histories<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<HandHistory>> {
+    let mut histories = vec![];
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            histories.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(histories)
+}
+
+/// Replays the round `recorded` by stacking a deck in the order the cards were originally dealt and re-driving a
+/// `ConsoleBlackjackTable` with the recorded actions. Returns a message for every way the replayed round diverged from the
+/// recording, so an empty vector means the engine reached exactly the same result.
+pub fn replay_round(recorded: &HandHistory) -> Result<Vec<String>, BlackjackGameError> {
+    let mut cards = recorded
+        .cards
+        .iter()
+        .map(|c| Card::from_str(c))
+        .collect::<Result<Vec<Card>, BlackjackGameError>>()?;
+    // Should the engine draw more cards than were recorded, they are dealt from an ordered deck so replays stay deterministic
+    for suit in SUITS {
+        for rank in RANKS {
+            cards.push(Card::new(suit, rank));
+        }
+    }
+
+    let mut player = ConsolePlayer::new(recorded.player.clone(), REPLAY_BALANCE);
+    let mut table = ConsoleBlackjackTable::with_deck(
+        REPLAY_BALANCE,
+        Deck::from_cards(cards),
+        1,
+        recorded.variant,
+    );
+    table.set_hole_card_rule(recorded.hole_card);
+    table.set_house_rules(recorded.rules);
+    // Variants dealing several hands per bet place the bet for every hand at once, e.g. Blackjack Switch
+    for bet in recorded
+        .bets
+        .iter()
+        .step_by(recorded.variant.hands_per_bet())
+    {
+        table.place_bet(&mut player, *bet as f32)?;
+    }
+    // The outcome paid is offered at the multiple it was recorded with, rounds recorded without one used the usual paytable
+    for side_bet in &recorded.side_bets {
+        let mut paytable = side_bet.side_bet.default_paytable();
+        if let (Some(outcome), Some(multiplier)) = (&side_bet.outcome, side_bet.multiplier) {
+            paytable.set_payout(outcome, multiplier);
+        }
+        table.offer_side_bet(side_bet.side_bet, paytable);
+        table.place_side_bet(&mut player, side_bet.side_bet, side_bet.bet)?;
+    }
+    table.deal_hand(&mut player);
+
+    let mut divergences = vec![];
+    let mut actions = recorded.actions.iter().peekable();
+    while !player.turn_is_over() {
+        let options = table.playing_options(&player);
+        // Rounds recorded before insurance was offered went on without deciding on it, i.e. they declined it
+        let decline = options
+            .iter()
+            .find_map(|(k, o)| (*o == Action::NoInsurance).then_some(*k));
+        if let Some(decline) = decline {
+            if !actions
+                .peek()
+                .is_some_and(|a| matches!(a.action, Action::Insurance | Action::NoInsurance))
+            {
+                table.play_option(&mut player, &options, decline)?;
+                continue;
+            }
+        }
+        let Some(action) = actions.next() else {
+            divergences.push(String::from(
+                "the recording ended before the player finished playing",
+            ));
+            return Ok(divergences);
+        };
+
+        let option = options
+            .iter()
+            .find(|(_, o)| **o == action.action && action.hand == player.hand_idx)
+            .map(|(k, _)| *k);
+        match option {
+            Some(option) => table.play_option(&mut player, &options, option)?,
+            None => {
+                divergences.push(format!(
+                    "recorded action '{}' on hand #{} is not available on hand #{}",
+                    action.action,
+                    action.hand + 1,
+                    player.hand_idx + 1
+                ));
+                return Ok(divergences);
+            }
+        }
+    }
+    table.finish_hand(&mut player);
+
+    let replayed = table
+        .take_hand_history()
+        .expect("a finished round should have a hand history");
+    let unused_actions = actions.count();
+    if unused_actions > 0 {
+        divergences.push(format!(
+            "{unused_actions} recorded action(s) were not replayed"
+        ));
+    }
+    if replayed.cards != recorded.cards {
+        divergences.push(format!(
+            "cards dealt differ, recorded [{}], replayed [{}]",
+            recorded.cards.join(" "),
+            replayed.cards.join(" ")
+        ));
+    }
+    if replayed.settlements != recorded.settlements {
+        let describe = |history: &HandHistory| {
+            history
+                .settlements
+                .iter()
+                .map(|s| format!("hand #{} {} ${:.2}", s.hand + 1, s.result, s.net))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        divergences.push(format!(
+            "settlements differ, recorded [{}], replayed [{}]",
+            describe(recorded),
+            describe(&replayed)
+        ));
+    }
+
+    let side_bet_settlements = |history: &HandHistory| {
+        history
+            .side_bets
+            .iter()
+            .map(|s| {
+                let outcome = s.outcome.as_deref().unwrap_or("lost");
+                format!("{} {} ${:.2}", s.side_bet, outcome, s.net)
+            })
+            .collect::<Vec<String>>()
+    };
+    if side_bet_settlements(&replayed) != side_bet_settlements(recorded) {
+        divergences.push(format!(
+            "side bets differ, recorded [{}], replayed [{}]",
+            side_bet_settlements(recorded).join(", "),
+            side_bet_settlements(&replayed).join(", ")
+        ));
+    }
+
+    if replayed.insurance != recorded.insurance {
+        let describe = |insurance: Option<f32>| {
+            insurance.map_or(String::from("none"), |net| format!("${net:.2}"))
+        };
+        divergences.push(format!(
+            "insurance differs, recorded {}, replayed {}",
+            describe(recorded.insurance),
+            describe(replayed.insurance)
+        ));
+    }
+
+    Ok(divergences)
+}
+
+/// Replays every round recorded in the JSON Lines log at `path`, see `replay_round`
+pub fn replay_file<P: AsRef<Path>>(path: P) -> std::io::Result<ReplayReport> {
+    let histories = read_hand_histories(path)?;
+    let mut divergences = vec![];
+    for recorded in &histories {
+        println!(
+            "Replaying round #{} of shoe #{}",
+            recorded.round, recorded.shoe_id
+        );
+        let messages = match replay_round(recorded) {
+            Ok(messages) => messages,
+            Err(e) => vec![e.to_string()],
+        };
+        divergences.extend(messages.into_iter().map(|message| Divergence {
+            round: recorded.round,
+            message,
+        }));
+    }
+
+    Ok(ReplayReport {
+        rounds: histories.len(),
+        divergences,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::side_bet::SideBet;
+    use crate::testing::{play_round, stacked_table};
+
+    /// Plays a round where a $5 Lucky Ladies bet paying 6 times the bet for any 20 wins, and returns its history
+    fn lucky_ladies_round() -> HandHistory {
+        let mut config = GameConfig::default();
+        let paytable = SideBet::LuckyLadies
+            .paytable_with(&[("any 20", 6.0)])
+            .unwrap();
+        config.offer_side_bet(SideBet::LuckyLadies, paytable);
+        let (mut table, mut player) = stacked_table("HK S9 DQ C9", &config);
+        table
+            .place_side_bet(&mut player, SideBet::LuckyLadies, 5)
+            .unwrap();
+        play_round(&mut table, &mut player, &[Action::Stand])
+    }
+
+    #[test]
+    fn replays_side_bets_at_the_recorded_payout() {
+        let recorded = lucky_ladies_round();
+        assert_eq!(recorded.side_bets[0].net, 30.0);
+        assert_eq!(replay_round(&recorded).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn side_bets_paid_differently_diverge() {
+        let mut recorded = lucky_ladies_round();
+        recorded.side_bets[0].net = 20.0;
+        let divergences = replay_round(&recorded).unwrap();
+        assert_eq!(divergences.len(), 1);
+        assert!(divergences[0].starts_with("side bets differ"));
+    }
+}
@@ -0,0 +1,180 @@
+use crate::betting::BetSizing;
+use crate::config::GameConfig;
+use crate::simulator::{mean_and_sd, simulate};
+use crate::BlackjackGameError;
+
+/// Returns the risk of ruin of a bankroll of `bankroll` dollars, i.e. the chance of ever losing it all, for a game won
+/// at an average of `mean` dollars per round with a standard deviation of `sd` dollars per round. This is the
+/// classic `exp(-2 * mean * bankroll / sd^2)`, which assumes the rounds are independent and bet the same over an
+/// endless session. Without an advantage ruin is certain
+pub fn risk_of_ruin(mean: f32, sd: f32, bankroll: f32) -> f32 {
+    if mean <= 0.0 {
+        return 1.0;
+    }
+    f32::exp(-2.0 * mean * bankroll / sd.powi(2))
+}
+
+/// Returns the bankroll needed for a risk of ruin of `risk`, a fraction such as 0.05, for a game won at an average of
+/// `mean` dollars per round with a standard deviation of `sd` dollars per round, see `risk_of_ruin`. Returns None if
+/// there is no advantage, as no bankroll is then large enough
+pub fn bankroll_for_risk(mean: f32, sd: f32, risk: f32) -> Option<f32> {
+    (mean > 0.0 && risk > 0.0 && risk < 1.0).then(|| -sd.powi(2) * f32::ln(risk) / (2.0 * mean))
+}
+
+/// The risk of ruin of a betting scheme played with a bankroll, computed from the mean and standard deviation of its
+/// rounds and measured over a number of simulated sessions
+#[derive(Clone, Debug, PartialEq)]
+pub struct RiskReport {
+    /// The name of the betting scheme
+    pub betting: String,
+    /// Every bet of the scheme is the same, which the analytic risk of ruin assumes
+    pub flat: bool,
+    pub bankroll: f32,
+    /// The mean and standard deviation of the result of a round over every simulated session
+    pub mean: f32,
+    pub sd: f32,
+    /// The number of sessions simulated, and the most rounds played in each
+    pub trials: u32,
+    pub rounds: u32,
+    /// The number of sessions that could no longer cover the minimum bet before every round was played
+    pub ruined: u32,
+    /// The risk of ruin aimed for when sizing the bankroll, as a fraction
+    pub target: f32,
+}
+
+impl RiskReport {
+    /// Returns the risk of ruin computed from the mean and standard deviation of a round, see `risk_of_ruin`. None
+    /// unless the scheme bets flat, as the formula does not hold for bets that change from round to round
+    pub fn analytic_risk(&self) -> Option<f32> {
+        self.flat
+            .then(|| risk_of_ruin(self.mean, self.sd, self.bankroll))
+    }
+
+    /// Returns the fraction of the simulated sessions that were ruined
+    pub fn simulated_risk(&self) -> f32 {
+        self.ruined as f32 / self.trials as f32
+    }
+
+    /// Returns the bankroll needed for the target risk of ruin, see `bankroll_for_risk`. None unless the scheme bets
+    /// flat, or if it has no advantage
+    pub fn bankroll_for_target(&self) -> Option<f32> {
+        self.flat
+            .then(|| bankroll_for_risk(self.mean, self.sd, self.target))
+            .flatten()
+    }
+
+    /// Prints the report to the console
+    pub fn display(&self) {
+        println!("{}", "-".repeat(80));
+        println!("{:<26}{}", "Betting:", self.betting);
+        println!("{:<26}${:.2}", "Bankroll:", self.bankroll);
+        println!(
+            "{:<26}${:.3} mean, ${:.2} standard deviation",
+            "Result per round:", self.mean, self.sd
+        );
+        // The formula only holds for flat bets, other schemes are only measured by the simulated sessions
+        if let Some(analytic_risk) = self.analytic_risk() {
+            println!(
+                "{:<26}{:.2}%",
+                "Risk of ruin, analytic:",
+                100.0 * analytic_risk
+            );
+        }
+        println!(
+            "{:<26}{:.2}% ({} of {} sessions ruined within {} rounds)",
+            "Risk of ruin, simulated:",
+            100.0 * self.simulated_risk(),
+            self.ruined,
+            self.trials,
+            self.rounds
+        );
+        if self.flat {
+            println!(
+                "{:<26}{}",
+                format!("Bankroll for {:.1}%:", 100.0 * self.target),
+                self.bankroll_for_target()
+                    .map_or(String::from("none, the scheme has no advantage"), |b| {
+                        format!("${b:.2}")
+                    })
+            );
+        }
+    }
+}
+
+/// Measures the risk of ruin of a bankroll of `bankroll` dollars for a bot sizing its bets with `sizing` at a table set
+/// up by `config`. `trials` sessions of up to `rounds` rounds are simulated, each shuffled from its own seed following
+/// the configs seed, and a session is ruined once it can no longer cover the minimum bet. The mean and standard
+/// deviation of a round over every session give the analytic risk of ruin and the bankroll needed for `target` when
+/// the bot bets flat
+pub fn simulate_risk_of_ruin(
+    config: &GameConfig,
+    sizing: &BetSizing,
+    bankroll: f32,
+    trials: u32,
+    rounds: u32,
+    target: f32,
+) -> Result<RiskReport, BlackjackGameError> {
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let mut results: Vec<f32> = vec![];
+    let mut ruined = 0;
+    for trial in 0..trials {
+        let config = GameConfig {
+            starting_balance: bankroll,
+            seed: Some(seed.wrapping_add(trial as u64)),
+            ..config.clone()
+        };
+        let report = simulate(&config, sizing.clone(), rounds)?;
+        if report.busted {
+            ruined += 1;
+        }
+        results.extend(report.round_results());
+    }
+
+    let (mean, sd) = mean_and_sd(&results).unwrap_or_default();
+    Ok(RiskReport {
+        betting: sizing.to_string(),
+        flat: sizing.is_flat(),
+        bankroll,
+        mean,
+        sd,
+        trials,
+        rounds,
+        ruined,
+        target,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn risk_of_ruin_falls_with_the_bankroll() {
+        assert!((risk_of_ruin(1.0, 10.0, 100.0) - f32::exp(-2.0)).abs() < 1e-6);
+        assert!((risk_of_ruin(0.5, 10.0, 100.0) - f32::exp(-1.0)).abs() < 1e-6);
+        assert_eq!(risk_of_ruin(1.0, 10.0, 0.0), 1.0);
+        assert!(risk_of_ruin(1.0, 10.0, 200.0) < risk_of_ruin(1.0, 10.0, 100.0));
+    }
+
+    #[test]
+    fn ruin_is_certain_without_an_advantage() {
+        assert_eq!(risk_of_ruin(0.0, 10.0, 1_000_000.0), 1.0);
+        assert_eq!(risk_of_ruin(-0.05, 1.15, 1_000_000.0), 1.0);
+    }
+
+    #[test]
+    fn bankroll_for_risk_inverts_risk_of_ruin() {
+        let bankroll = bankroll_for_risk(1.0, 10.0, f32::exp(-2.0)).unwrap();
+        assert!((bankroll - 100.0).abs() < 1e-3);
+        let bankroll = bankroll_for_risk(0.02, 1.15, 0.05).unwrap();
+        assert!((risk_of_ruin(0.02, 1.15, bankroll) - 0.05).abs() < 1e-4);
+    }
+
+    #[test]
+    fn bankroll_for_risk_needs_an_advantage_and_a_risk_between_0_and_1() {
+        assert_eq!(bankroll_for_risk(0.0, 10.0, 0.05), None);
+        assert_eq!(bankroll_for_risk(-1.0, 10.0, 0.05), None);
+        assert_eq!(bankroll_for_risk(1.0, 10.0, 0.0), None);
+        assert_eq!(bankroll_for_risk(1.0, 10.0, 1.0), None);
+    }
+}
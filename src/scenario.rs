@@ -0,0 +1,187 @@
+use crate::{parse_cards, BlackjackGameError, Deck};
+
+/// A named, stacked first round used to demonstrate or test a particular situation at the table
+pub struct Scenario {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The first cards of the shoe, dealt player, dealer (hole card), player, dealer (up card) and then in order as needed
+    pub cards: &'static str,
+}
+
+pub const SCENARIOS: [Scenario; 4] = [
+    Scenario {
+        name: "dealer-blackjack",
+        description: "The dealer shows an ace and has blackjack",
+        cards: "H10 SK D7 SA",
+    },
+    Scenario {
+        name: "split-aces",
+        description: "The player is dealt a pair of aces against a dealer 6",
+        cards: "SA D7 HA C6 D10 C9",
+    },
+    Scenario {
+        name: "soft-17",
+        description: "The dealer has a soft 17 against the players 18",
+        cards: "H10 SA C8 H6",
+    },
+    Scenario {
+        name: "multi-split",
+        description:
+            "The player is dealt a pair of eights and keeps receiving eights to split again",
+        cards: "S8 D10 H8 C6 D8 C8 H3 S2",
+    },
+];
+
+impl Scenario {
+    /// Returns the scenario named `name`, if there is one
+    pub fn find(name: &str) -> Option<&'static Scenario> {
+        SCENARIOS.iter().find(|s| s.name == name)
+    }
+
+    /// Builds a shoe of `n_decks` decks that deals the scenario first, followed by the rest of the shoe in a random order
+    /// shuffled from `seed` if there is one
+    pub fn deck(&self, n_decks: usize, seed: Option<u64>) -> Result<Deck, BlackjackGameError> {
+        Deck::with_prefix(parse_cards(self.cards)?, n_decks, seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::Action;
+    use crate::config::GameConfig;
+    use crate::history::HandResult;
+    use crate::side_bet::SideBet;
+    use crate::testing::{play_action, play_round, results, stacked_table};
+    use crate::variant::GameVariant;
+    use crate::BlackjackTable;
+
+    #[test]
+    fn dealer_blackjack_takes_the_bet() {
+        let scenario = Scenario::find("dealer-blackjack").unwrap();
+        let (mut table, mut player) = stacked_table(scenario.cards, &GameConfig::default());
+        let history = play_round(&mut table, &mut player, &[Action::NoInsurance]);
+        assert_eq!(results(&history), vec![(HandResult::Lost, -10.0)]);
+        assert_eq!(history.insurance, None);
+        assert_eq!(player.balance, 490.0);
+    }
+
+    #[test]
+    fn insurance_pays_two_to_one_against_dealer_blackjack() {
+        let scenario = Scenario::find("dealer-blackjack").unwrap();
+        let (mut table, mut player) = stacked_table(scenario.cards, &GameConfig::default());
+        let history = play_round(&mut table, &mut player, &[Action::Insurance]);
+        assert_eq!(results(&history), vec![(HandResult::Lost, -10.0)]);
+        assert_eq!(history.insurance, Some(10.0));
+        assert_eq!(player.balance, 500.0);
+    }
+
+    #[test]
+    fn insurance_is_lost_when_the_dealer_has_no_blackjack() {
+        let (mut table, mut player) = stacked_table("H10 S7 D8 SA", &GameConfig::default());
+        let history = play_round(&mut table, &mut player, &[Action::Insurance, Action::Stand]);
+        assert_eq!(results(&history), vec![(HandResult::Pushed, 0.0)]);
+        assert_eq!(history.insurance, Some(-5.0));
+        assert_eq!(player.balance, 495.0);
+        assert_eq!(history.actions[0].action, Action::Insurance);
+    }
+
+    #[test]
+    fn split_aces_win_both_hands() {
+        // The dealer draws a five to stand on 18 against the players 21 and 20
+        let (mut table, mut player) =
+            stacked_table("SA D7 HA C6 D10 C9 S5", &GameConfig::default());
+        let history = play_round(
+            &mut table,
+            &mut player,
+            &[Action::Split, Action::Stand, Action::Stand],
+        );
+        assert!(history.settlements.iter().all(|s| s.bet == 10));
+        assert_eq!(
+            results(&history),
+            vec![(HandResult::Won, 10.0), (HandResult::Won, 10.0)]
+        );
+        assert_eq!(player.balance, 520.0);
+        assert_eq!((player.stats.wins, player.stats.losses), (2, 0));
+    }
+
+    #[test]
+    fn dealer_stands_or_hits_soft_17() {
+        let scenario = Scenario::find("soft-17").unwrap();
+        let (mut table, mut player) = stacked_table(scenario.cards, &GameConfig::default());
+        let history = play_round(&mut table, &mut player, &[Action::Stand]);
+        assert_eq!(results(&history), vec![(HandResult::Won, 10.0)]);
+
+        // Hitting the soft 17 draws a four to beat the players 18
+        let mut config = GameConfig::default();
+        config.rules.dealer_hits_soft_17 = true;
+        let cards = format!("{} S4", scenario.cards);
+        let (mut table, mut player) = stacked_table(&cards, &config);
+        let history = play_round(&mut table, &mut player, &[Action::Stand]);
+        assert_eq!(results(&history), vec![(HandResult::Lost, -10.0)]);
+        assert_eq!(history.dealer_hand, vec!["SA", "H6", "S4"]);
+    }
+
+    #[test]
+    fn player_blackjack_pays_three_to_two() {
+        let (mut table, mut player) = stacked_table("SA D9 HK C7", &GameConfig::default());
+        let history = play_round(&mut table, &mut player, &[]);
+        assert_eq!(results(&history), vec![(HandResult::Blackjack, 15.0)]);
+        assert_eq!(player.balance, 515.0);
+    }
+
+    #[test]
+    fn perfect_pairs_settles_at_the_deal() {
+        let mut config = GameConfig::default();
+        config.offer_side_bet(
+            SideBet::PerfectPairs,
+            SideBet::PerfectPairs.default_paytable(),
+        );
+        let (mut table, mut player) = stacked_table("H8 D10 D8 C6 S10", &config);
+        table
+            .place_side_bet(&mut player, SideBet::PerfectPairs, 5)
+            .unwrap();
+        let history = play_round(&mut table, &mut player, &[Action::Stand]);
+        assert_eq!(results(&history), vec![(HandResult::Won, 10.0)]);
+        assert_eq!(history.side_bets.len(), 1);
+        assert_eq!(
+            history.side_bets[0].outcome.as_deref(),
+            Some("colored pair")
+        );
+        assert_eq!(history.side_bets[0].net, 60.0);
+        assert_eq!(player.balance, 570.0);
+    }
+
+    #[test]
+    fn pontoon_buys_the_original_bet_until_the_hand_twists() {
+        let config = GameConfig {
+            variant: GameVariant::Pontoon,
+            ..GameConfig::default()
+        };
+        let (mut table, mut player) = stacked_table("H5 D10 C4 S7 S2 D3", &config);
+        table.deal_hand(&mut player);
+        for action in [Action::Buy, Action::Twist] {
+            let options = table.playing_options(&player);
+            assert!(options.values().any(|o| *o == Action::Buy));
+            play_action(&mut table, &mut player, action);
+        }
+        assert_eq!(*player.bets(), vec![20]);
+        let options = table.playing_options(&player);
+        assert!(!options.values().any(|o| *o == Action::Buy));
+    }
+
+    #[test]
+    fn every_scenario_deals_its_cards_first() {
+        for scenario in &SCENARIOS {
+            let deck = scenario.deck(6, Some(7)).unwrap();
+            let cards: Vec<String> = deck
+                .remaining_cards()
+                .iter()
+                .map(|c| c.to_string())
+                .collect();
+            let expected: Vec<&str> = scenario.cards.split_whitespace().collect();
+            assert_eq!(cards[..expected.len()], expected);
+            assert_eq!(cards.len(), 6 * 52);
+        }
+    }
+}
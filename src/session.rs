@@ -0,0 +1,228 @@
+use crate::action::Action;
+use crate::history::{HandHistory, HandResult};
+
+/// The levels a sparkline is drawn with, from lowest to highest
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// The levels a sparkline is drawn with when suit symbols and other unicode are turned off
+const ASCII_SPARK_LEVELS: [char; 8] = ['_', '.', ',', '-', '~', '=', '*', '#'];
+/// The widest a bankroll sparkline is drawn
+const SPARKLINE_WIDTH: usize = 60;
+
+/// Returns `values` drawn as a sparkline of at most `width` characters, one per value. Longer series are sampled evenly,
+/// always keeping the last value
+pub fn sparkline(values: &[f32], width: usize, unicode: bool) -> String {
+    let levels = if unicode {
+        SPARK_LEVELS
+    } else {
+        ASCII_SPARK_LEVELS
+    };
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+
+    let sampled: Vec<f32> = if values.len() <= width {
+        values.to_vec()
+    } else {
+        (0..width)
+            .map(|i| values[i * (values.len() - 1) / (width - 1).max(1)])
+            .collect()
+    };
+    let min = sampled.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = sampled.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    sampled
+        .iter()
+        .map(|v| {
+            // A flat bankroll is drawn through the middle
+            let level = if max > min {
+                ((v - min) / (max - min) * (levels.len() - 1) as f32).round() as usize
+            } else {
+                levels.len() / 2
+            };
+            levels[level]
+        })
+        .collect()
+}
+
+/// The number of hands won, lost and pushed along with their net result, for a kind of hand such as doubled hands
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HandRecord {
+    pub won: u32,
+    pub lost: u32,
+    pub pushed: u32,
+    pub net: f32,
+}
+
+impl HandRecord {
+    /// Records a hand settled with `result` and net result `net`
+    fn record(&mut self, result: HandResult, net: f32) {
+        match result {
+            HandResult::Blackjack | HandResult::Won => self.won += 1,
+            HandResult::Pushed => self.pushed += 1,
+            HandResult::Lost | HandResult::Surrendered => self.lost += 1,
+        }
+        self.net += net;
+    }
+
+    /// Returns the number of hands recorded
+    pub fn hands(&self) -> u32 {
+        self.won + self.lost + self.pushed
+    }
+}
+
+/// Statistics of a single session at the table, i.e. every round played since the game started
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionStats {
+    pub rounds: u32,
+    /// Every hand played, split hands counting as separate hands
+    pub hands: HandRecord,
+    pub blackjacks: u32,
+    pub doubles: HandRecord,
+    /// The hands of every round where the player split
+    pub splits: HandRecord,
+    pub side_bets_net: f32,
+    /// The sum of the bets placed at the start of every hand, before doubling or splitting
+    pub total_bet: u64,
+    pub n_bets: u32,
+    pub biggest_win: f32,
+    /// The most lost in a single round, as a positive amount
+    pub biggest_loss: f32,
+    /// The players balance at the start of the session and after every round
+    pub bankroll: Vec<f32>,
+}
+
+impl SessionStats {
+    /// Starts a session for a player with balance `balance`
+    pub fn new(balance: f32) -> SessionStats {
+        SessionStats {
+            rounds: 0,
+            hands: HandRecord::default(),
+            blackjacks: 0,
+            doubles: HandRecord::default(),
+            splits: HandRecord::default(),
+            side_bets_net: 0.0,
+            total_bet: 0,
+            n_bets: 0,
+            biggest_win: 0.0,
+            biggest_loss: 0.0,
+            bankroll: vec![balance],
+        }
+    }
+
+    /// Records the round `history`, after which the player was left with balance `balance`
+    pub fn record_round(&mut self, history: &HandHistory, balance: f32) {
+        let net = balance - self.balance();
+        self.rounds += 1;
+        self.biggest_win = f32::max(self.biggest_win, net);
+        self.biggest_loss = f32::max(self.biggest_loss, -net);
+        self.bankroll.push(balance);
+        self.total_bet += history.bets.iter().map(|b| *b as u64).sum::<u64>();
+        self.n_bets += history.bets.len() as u32;
+        self.side_bets_net += history.side_bets.iter().map(|s| s.net).sum::<f32>();
+
+        let actions: Vec<(usize, Action)> =
+            history.actions.iter().map(|a| (a.hand, a.action)).collect();
+        let split = actions
+            .iter()
+            .any(|(_, a)| matches!(a, Action::Split | Action::FreeSplit));
+        for settlement in &history.settlements {
+            self.hands.record(settlement.result, settlement.net);
+            if settlement.result == HandResult::Blackjack {
+                self.blackjacks += 1;
+            }
+            let doubled = actions.iter().any(|(hand, a)| {
+                *hand == settlement.hand && matches!(a, Action::DoubleDown | Action::FreeDouble)
+            });
+            if doubled {
+                self.doubles.record(settlement.result, settlement.net);
+            }
+            if split {
+                self.splits.record(settlement.result, settlement.net);
+            }
+        }
+    }
+
+    /// Returns the players current balance
+    pub fn balance(&self) -> f32 {
+        *self
+            .bankroll
+            .last()
+            .expect("bankroll starts with a balance")
+    }
+
+    /// Returns the net amount won over the session, negative if the player is down
+    pub fn net_result(&self) -> f32 {
+        self.balance() - self.bankroll[0]
+    }
+
+    /// Returns the percentage of hands won, or None if no hands have been played
+    pub fn win_rate(&self) -> Option<f32> {
+        let hands = self.hands.hands();
+        (hands > 0).then(|| 100.0 * self.hands.won as f32 / hands as f32)
+    }
+
+    /// Returns the average bet placed at the start of a hand, or None if no bets have been placed
+    pub fn average_bet(&self) -> Option<f32> {
+        (self.n_bets > 0).then(|| self.total_bet as f32 / self.n_bets as f32)
+    }
+
+    /// Returns the largest fall in the players balance from a high to a later low over the session
+    pub fn max_drawdown(&self) -> f32 {
+        let mut peak = f32::NEG_INFINITY;
+        let mut drawdown = 0.0;
+        for balance in &self.bankroll {
+            peak = f32::max(peak, *balance);
+            drawdown = f32::max(drawdown, peak - balance);
+        }
+        drawdown
+    }
+
+    /// Prints the session report to the console, with the bankroll sparkline drawn with unicode blocks if `unicode` is true
+    pub fn display(&self, unicode: bool) {
+        let record =
+            |r: &HandRecord| format!("{}/{}/{}, net ${:.2}", r.won, r.lost, r.pushed, r.net);
+        println!("{}", "-".repeat(80));
+        println!("Session summary");
+        println!("{:<20}{}", "Rounds played:", self.rounds);
+        println!("{:<20}{}", "Hands played:", self.hands.hands());
+        println!("{:<20}{}", "Won/Lost/Pushed:", record(&self.hands));
+        println!(
+            "{:<20}{}",
+            "Win rate:",
+            self.win_rate()
+                .map_or(String::from("n/a"), |w| format!("{w:.1}%"))
+        );
+        println!("{:<20}{}", "Blackjacks:", self.blackjacks);
+        println!(
+            "{:<20}{} hand(s), {}",
+            "Doubles:",
+            self.doubles.hands(),
+            record(&self.doubles)
+        );
+        println!(
+            "{:<20}{} hand(s), {}",
+            "Splits:",
+            self.splits.hands(),
+            record(&self.splits)
+        );
+        if self.side_bets_net != 0.0 {
+            println!("{:<20}${:.2}", "Side bets net:", self.side_bets_net);
+        }
+        println!(
+            "{:<20}{}",
+            "Average bet:",
+            self.average_bet()
+                .map_or(String::from("n/a"), |b| format!("${b:.2}"))
+        );
+        println!("{:<20}${:.2}", "Net result:", self.net_result());
+        println!("{:<20}${:.2}", "Biggest win:", self.biggest_win);
+        println!("{:<20}${:.2}", "Biggest loss:", self.biggest_loss);
+        println!("{:<20}${:.2}", "Max drawdown:", self.max_drawdown());
+        println!(
+            "{:<20}{}  ${:.2} -> ${:.2}",
+            "Bankroll:",
+            sparkline(&self.bankroll, SPARKLINE_WIDTH, unicode),
+            self.bankroll[0],
+            self.balance()
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use crate::{BlackjackGameError, Card, RANKS};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::rc::Rc;
+
+/// An optional bet placed alongside the players main bet, settled on the players first two cards and the dealers cards
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum,
+)]
+pub enum SideBet {
+    /// Pays when the players first two cards are a pair, more for a pair of the same colour or suit
+    PerfectPairs,
+    /// Pays on the poker hand made by the players first two cards and the dealers up card
+    #[value(alias = "21+3")]
+    TwentyOnePlusThree,
+    /// Pays when the players first two cards total 20, more for suited or matched cards and the most for a pair of queens
+    /// of hearts, when it also matters whether the dealer has blackjack
+    LuckyLadies,
+    /// Pays when the dealer busts, by the number of cards in the dealers busted hand
+    BusterBlackjack,
+}
+
+impl SideBet {
+    /// Returns the side bets usual paytable with the multiples of the bet in `payouts` paid instead, or an error if
+    /// `payouts` names an outcome the side bet does not have or a multiple that is not a positive number
+    pub fn paytable_with(&self, payouts: &[(&str, f32)]) -> Result<Paytable, BlackjackGameError> {
+        let mut paytable = self.default_paytable();
+        for (outcome, multiplier) in payouts {
+            if paytable.payout(outcome).is_none() {
+                return Err(BlackjackGameError::new(format!(
+                    "'{outcome}' is not an outcome of {self}, the outcomes are {}",
+                    paytable.outcomes().collect::<Vec<&str>>().join(", ")
+                )));
+            }
+            if !multiplier.is_finite() || *multiplier <= 0.0 {
+                return Err(BlackjackGameError::new(format!(
+                    "{self} cannot pay {multiplier} times the bet for '{outcome}'"
+                )));
+            }
+            paytable.set_payout(outcome, *multiplier);
+        }
+        Ok(paytable)
+    }
+
+    /// Returns the paytable the side bet is usually offered with
+    pub fn default_paytable(&self) -> Paytable {
+        match self {
+            SideBet::PerfectPairs => Paytable::new(&[
+                ("mixed pair", 6.0),
+                ("colored pair", 12.0),
+                ("perfect pair", 25.0),
+            ]),
+            SideBet::TwentyOnePlusThree => Paytable::new(&[
+                ("flush", 5.0),
+                ("straight", 10.0),
+                ("three of a kind", 30.0),
+                ("straight flush", 40.0),
+                ("suited trips", 100.0),
+            ]),
+            SideBet::LuckyLadies => Paytable::new(&[
+                ("any 20", 4.0),
+                ("suited 20", 9.0),
+                ("matched 20", 19.0),
+                ("queen of hearts pair", 125.0),
+                ("queen of hearts pair with dealer blackjack", 1000.0),
+            ]),
+            SideBet::BusterBlackjack => Paytable::new(&[
+                ("3 card bust", 1.0),
+                ("4 card bust", 2.0),
+                ("5 card bust", 4.0),
+                ("6 card bust", 12.0),
+                ("7 card bust", 50.0),
+                ("8+ card bust", 250.0),
+            ]),
+        }
+    }
+
+    /// Returns true if the side bet is settled as soon as the cards are dealt, rather than once the dealer has finished
+    /// their hand
+    pub fn settles_at_deal(&self) -> bool {
+        matches!(self, SideBet::PerfectPairs | SideBet::TwentyOnePlusThree)
+    }
+
+    /// Returns the winning outcome of the side bet for the players first two cards `players_cards`, the dealers up card
+    /// `up_card` and the dealers cards `dealers_cards`, or None if the side bet lost. Side bets that do not settle at the deal
+    /// expect `dealers_cards` to be the dealers final hand
+    pub fn outcome(
+        &self,
+        players_cards: &[Rc<Card>],
+        up_card: &Card,
+        dealers_cards: &[Rc<Card>],
+    ) -> Option<&'static str> {
+        let (first, second) = (&players_cards[0], &players_cards[1]);
+        match self {
+            SideBet::PerfectPairs if first.rank != second.rank => None,
+            SideBet::PerfectPairs if first.suit == second.suit => Some("perfect pair"),
+            SideBet::PerfectPairs if is_red(first) == is_red(second) => Some("colored pair"),
+            SideBet::PerfectPairs => Some("mixed pair"),
+            SideBet::TwentyOnePlusThree => {
+                let cards = [first.as_ref(), second.as_ref(), up_card];
+                let flush = cards.iter().all(|c| c.suit == first.suit);
+                let trips = cards.iter().all(|c| c.rank == first.rank);
+                match (flush, trips, is_straight(&cards)) {
+                    (true, true, _) => Some("suited trips"),
+                    (true, _, true) => Some("straight flush"),
+                    (_, true, _) => Some("three of a kind"),
+                    (_, _, true) => Some("straight"),
+                    (true, _, _) => Some("flush"),
+                    _ => None,
+                }
+            }
+            SideBet::LuckyLadies if total(&players_cards[..2]) != 20 => None,
+            SideBet::LuckyLadies
+                if [first, second]
+                    .iter()
+                    .all(|c| c.suit == "H" && c.rank == "Q") =>
+            {
+                match is_blackjack(dealers_cards) {
+                    true => Some("queen of hearts pair with dealer blackjack"),
+                    false => Some("queen of hearts pair"),
+                }
+            }
+            SideBet::LuckyLadies if first.suit == second.suit && first.rank == second.rank => {
+                Some("matched 20")
+            }
+            SideBet::LuckyLadies if first.suit == second.suit => Some("suited 20"),
+            SideBet::LuckyLadies => Some("any 20"),
+            SideBet::BusterBlackjack if total(dealers_cards) <= 21 => None,
+            SideBet::BusterBlackjack => match dealers_cards.len() {
+                n if n <= 3 => Some("3 card bust"),
+                4 => Some("4 card bust"),
+                5 => Some("5 card bust"),
+                6 => Some("6 card bust"),
+                7 => Some("7 card bust"),
+                _ => Some("8+ card bust"),
+            },
+        }
+    }
+}
+
+impl Display for SideBet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SideBet::PerfectPairs => "Perfect Pairs",
+            SideBet::TwentyOnePlusThree => "21+3",
+            SideBet::LuckyLadies => "Lucky Ladies",
+            SideBet::BusterBlackjack => "Buster Blackjack",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Returns true if `card` is a heart or a diamond
+fn is_red(card: &Card) -> bool {
+    card.suit == "H" || card.suit == "D"
+}
+
+/// Returns the best total of `cards`, counting an ace as 11 when that does not bust the hand
+pub(crate) fn total(cards: &[Rc<Card>]) -> u8 {
+    let total: u8 = cards.iter().map(|c| c.val).sum();
+    if cards.iter().any(|c| c.rank == "A") && total <= 11 {
+        total + 10
+    } else {
+        total
+    }
+}
+
+/// Returns true if `cards` are a blackjack, i.e. an ace and a ten valued card
+fn is_blackjack(cards: &[Rc<Card>]) -> bool {
+    cards.len() == 2 && total(cards) == 21
+}
+
+/// Returns true if the ranks of `cards` are consecutive, an ace counts as either low or high
+fn is_straight(cards: &[&Card]) -> bool {
+    let mut ranks: Vec<usize> = cards
+        .iter()
+        .map(|c| RANKS.iter().position(|r| *r == c.rank).unwrap_or_default())
+        .collect();
+    ranks.sort();
+    let consecutive = |ranks: &[usize]| ranks.windows(2).all(|w| w[1] == w[0] + 1);
+    // With the ace high, i.e. queen, king, ace
+    consecutive(&ranks) || ranks == [0, RANKS.len() - 2, RANKS.len() - 1]
+}
+
+/// The multiple of the bet paid for each winning outcome of a side bet
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Paytable {
+    payouts: BTreeMap<String, f32>,
+}
+
+impl Paytable {
+    /// Creates a new paytable paying the multiple of the bet given with each outcome
+    pub fn new(payouts: &[(&str, f32)]) -> Paytable {
+        Paytable {
+            payouts: payouts
+                .iter()
+                .map(|(outcome, multiplier)| (outcome.to_string(), *multiplier))
+                .collect(),
+        }
+    }
+
+    /// Returns the multiple of the bet paid for `outcome`, or None if the paytable does not pay the outcome
+    pub fn payout(&self, outcome: &str) -> Option<f32> {
+        self.payouts.get(outcome).copied()
+    }
+
+    /// Sets the multiple of the bet paid for `outcome`
+    pub fn set_payout(&mut self, outcome: &str, multiplier: f32) {
+        self.payouts.insert(outcome.to_string(), multiplier);
+    }
+
+    /// Returns the outcomes the paytable pays
+    pub fn outcomes(&self) -> impl Iterator<Item = &str> {
+        self.payouts.keys().map(|outcome| outcome.as_str())
+    }
+
+    /// Returns the largest multiple of the bet the paytable pays
+    pub fn max_payout(&self) -> f32 {
+        self.payouts.values().copied().fold(0.0, f32::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+
+    /// Returns the cards of `cards`
+    fn cards(cards: &str) -> Vec<Rc<Card>> {
+        parse_cards(cards)
+            .unwrap()
+            .into_iter()
+            .map(Rc::new)
+            .collect()
+    }
+
+    /// Returns the outcome of `side_bet` for the players cards `players` and the dealers cards `dealers`, the first of
+    /// which is the up card
+    fn outcome(side_bet: SideBet, players: &str, dealers: &str) -> Option<&'static str> {
+        let dealers = cards(dealers);
+        side_bet.outcome(&cards(players), &dealers[0], &dealers)
+    }
+
+    #[test]
+    fn perfect_pairs_pays_by_the_colour_and_suit_of_the_pair() {
+        let outcome = |players| outcome(SideBet::PerfectPairs, players, "C2 D9");
+        assert_eq!(outcome("H8 S8"), Some("mixed pair"));
+        assert_eq!(outcome("H8 D8"), Some("colored pair"));
+        assert_eq!(outcome("S8 S8"), Some("perfect pair"));
+        assert_eq!(outcome("S8 S9"), None);
+    }
+
+    #[test]
+    fn twenty_one_plus_three_pays_the_poker_hand_with_the_up_card() {
+        let outcome = |players, up_card| outcome(SideBet::TwentyOnePlusThree, players, up_card);
+        assert_eq!(outcome("H2 H9", "HK"), Some("flush"));
+        assert_eq!(outcome("H9 S10", "DJ"), Some("straight"));
+        assert_eq!(outcome("H9 S9", "D9"), Some("three of a kind"));
+        assert_eq!(outcome("H9 H10", "HJ"), Some("straight flush"));
+        assert_eq!(outcome("H9 H9", "H9"), Some("suited trips"));
+        assert_eq!(outcome("H2 S9", "DK"), None);
+    }
+
+    #[test]
+    fn straights_count_the_ace_low_or_high_but_do_not_wrap() {
+        let straight = |sequence| {
+            is_straight(
+                &cards(sequence)
+                    .iter()
+                    .map(|c| c.as_ref())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        assert!(straight("SA H2 D3"));
+        assert!(straight("SQ HK DA"));
+        assert!(straight("D3 SA H2"));
+        assert!(!straight("SK HA D2"));
+        assert!(!straight("S2 H3 D5"));
+    }
+
+    #[test]
+    fn lucky_ladies_pays_a_20_by_its_suits_and_ranks() {
+        let outcome = |players, dealers| outcome(SideBet::LuckyLadies, players, dealers);
+        assert_eq!(outcome("HK S10", "C7 D9"), Some("any 20"));
+        assert_eq!(outcome("HK HJ", "C7 D9"), Some("suited 20"));
+        assert_eq!(outcome("SK SK", "C7 D9"), Some("matched 20"));
+        assert_eq!(outcome("HQ HQ", "C7 D9"), Some("queen of hearts pair"));
+        assert_eq!(
+            outcome("HQ HQ", "CA DK"),
+            Some("queen of hearts pair with dealer blackjack")
+        );
+        assert_eq!(outcome("DQ DQ", "CA DK"), Some("matched 20"));
+        assert_eq!(outcome("HA H9", "C7 D9"), Some("suited 20"));
+        assert_eq!(outcome("HK H9", "C7 D9"), None);
+    }
+
+    #[test]
+    fn buster_blackjack_pays_by_the_cards_in_the_dealers_bust() {
+        let outcome = |dealers| outcome(SideBet::BusterBlackjack, "H8 S9", dealers);
+        assert_eq!(outcome("C6 DK SQ"), Some("3 card bust"));
+        assert_eq!(outcome("C2 D4 HK SQ"), Some("4 card bust"));
+        assert_eq!(outcome("C2 D2 H2 SK SQ"), Some("5 card bust"));
+        assert_eq!(outcome("C2 D2 H2 S2 H6 SQ"), Some("6 card bust"));
+        assert_eq!(outcome("C2 D2 H2 S2 C3 D3 SK"), Some("7 card bust"));
+        assert_eq!(outcome("C2 D2 H2 S2 CA DA H3 SQ"), Some("8+ card bust"));
+        assert_eq!(
+            outcome("CA DA HA SA C2 D2 H2 S2 H3 SQ"),
+            Some("8+ card bust")
+        );
+        assert_eq!(outcome("C6 DK"), None);
+        assert_eq!(outcome("C6 D5 HK"), None);
+    }
+
+    #[test]
+    fn paytable_with_rejects_unknown_outcomes_and_bad_payouts() {
+        let paytable = SideBet::LuckyLadies
+            .paytable_with(&[("any 20", 5.0)])
+            .unwrap();
+        assert_eq!(paytable.payout("any 20"), Some(5.0));
+        assert_eq!(paytable.payout("matched 20"), Some(19.0));
+        assert!(SideBet::LuckyLadies
+            .paytable_with(&[("any 21", 4.0)])
+            .is_err());
+        assert!(SideBet::LuckyLadies
+            .paytable_with(&[("any 20", 0.0)])
+            .is_err());
+    }
+}
@@ -0,0 +1,347 @@
+use crate::action::Action;
+use crate::betting::BetSizing;
+use crate::config::GameConfig;
+use crate::console::{player::ConsolePlayer, table::ConsoleBlackjackTable};
+use crate::session::{sparkline, SessionStats};
+use crate::{compute_optimal_hand, table_from_config, BlackjackGameError, BlackjackTable};
+use std::collections::HashMap;
+
+/// The widest a bankroll sparkline is drawn in a simulation report
+const SPARKLINE_WIDTH: usize = 40;
+
+/// Returns the option a bot plays for `player`'s current hand, the basic strategy play at a Standard table. Other
+/// variants have no chart, so the bot declines insurance, keeps the hands it was dealt, draws below 17 and otherwise
+/// stands
+pub fn bot_option(
+    table: &ConsoleBlackjackTable,
+    player: &ConsolePlayer,
+    options: &HashMap<i32, Action>,
+) -> i32 {
+    let find = |plays: &[Action]| {
+        plays
+            .iter()
+            .find_map(|play| options.iter().find(|(_, o)| *o == play))
+            .map(|(i, _)| *i)
+    };
+    if let Some(action) = table.suggested_option(player, options) {
+        if let Some(i) = find(&[action]) {
+            return i;
+        }
+    }
+
+    let hand_value = compute_optimal_hand(&player.bj_hand.hand_values[player.hand_idx]);
+    let plays = if hand_value < 17 {
+        [
+            Action::NoInsurance,
+            Action::Keep,
+            Action::Hit,
+            Action::Twist,
+            Action::Stand,
+            Action::Stick,
+        ]
+    } else {
+        // A Pontoon hand can only stick from 15, so there is always a draw to fall back on
+        [
+            Action::NoInsurance,
+            Action::Keep,
+            Action::Stand,
+            Action::Stick,
+            Action::Hit,
+            Action::Twist,
+        ]
+    };
+    find(&plays).expect("player should be able to stand or draw")
+}
+
+/// Returns the mean and standard deviation of `results`, or None if there are none
+pub fn mean_and_sd(results: &[f32]) -> Option<(f32, f32)> {
+    if results.is_empty() {
+        return None;
+    }
+    let n = results.len() as f64;
+    let mean = results.iter().map(|r| *r as f64).sum::<f64>() / n;
+    let variance = results
+        .iter()
+        .map(|r| (*r as f64 - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    Some((mean as f32, variance.sqrt() as f32))
+}
+
+/// A seat at a table played by a bot, which sizes its bets with a betting scheme and plays its hands with `bot_option`
+pub struct BotSeat {
+    player: ConsolePlayer,
+    sizing: BetSizing,
+    session: SessionStats,
+    /// The rounds where the bet called for was more than the table limits or the balance allowed
+    capped_rounds: u32,
+    largest_bet: u32,
+}
+
+impl BotSeat {
+    /// Returns a bot named `name` with balance `balance`, sizing its bets with `sizing`
+    pub fn new(name: String, balance: f32, sizing: BetSizing) -> BotSeat {
+        BotSeat {
+            player: ConsolePlayer::new(name, balance),
+            sizing,
+            session: SessionStats::new(balance),
+            capped_rounds: 0,
+            largest_bet: 0,
+        }
+    }
+
+    /// Returns the bots player, i.e. its balance and lifetime statistics
+    pub fn player(&self) -> &ConsolePlayer {
+        &self.player
+    }
+
+    /// Plays a round at `table`, betting what the bots betting scheme calls for within the tables limits. Returns false
+    /// without playing if the bot can no longer cover the minimum bet, or an error if the table refused the bet
+    pub fn play_round(
+        &mut self,
+        table: &mut ConsoleBlackjackTable,
+    ) -> Result<bool, BlackjackGameError> {
+        // Every bet is placed once for each hand the variant deals, so the balance has to cover all of them
+        let n_hands = table.variant().hands_per_bet() as f32;
+        let balance = self.player.balance / n_hands;
+        // Once the cut card is out the shoe is shuffled before the next round, so the count starts over
+        let true_count = if table.deck().shuffle_flag {
+            0.0
+        } else {
+            table.true_count()
+        };
+        let Some(bet) = self
+            .sizing
+            .next_bet(table.bet_limits(), balance, true_count)
+        else {
+            return Ok(false);
+        };
+        if bet < self.sizing.desired_bet(true_count, balance) {
+            self.capped_rounds += 1;
+        }
+        self.largest_bet = u32::max(self.largest_bet, bet);
+        table.place_bet(&mut self.player, bet as f32)?;
+
+        table.deal_hand(&mut self.player);
+        while !self.player.turn_is_over() {
+            let options = table.playing_options(&self.player);
+            let option = bot_option(table, &self.player, &options);
+            table.play_option(&mut self.player, &options, option)?;
+        }
+        table.finish_hand(&mut self.player);
+        // A quiet table keeps everything it would have printed, which nobody is reading
+        table.take_messages();
+
+        let net = self.player.balance - self.session.balance();
+        if let Some(history) = table.take_hand_history() {
+            self.session.record_round(&history, self.player.balance);
+        }
+        self.sizing.record_result(net);
+        Ok(true)
+    }
+
+    /// Returns the report of every round the bot has played
+    pub fn report(self) -> SimulationReport {
+        SimulationReport {
+            betting: self.sizing.to_string(),
+            session: self.session,
+            capped_rounds: self.capped_rounds,
+            largest_bet: self.largest_bet,
+            busted: false,
+        }
+    }
+}
+
+/// The results of a bot playing a betting scheme over a run of rounds
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationReport {
+    /// The name of the betting scheme
+    pub betting: String,
+    pub session: SessionStats,
+    /// The rounds where the bet called for was more than the table limits or the balance allowed
+    pub capped_rounds: u32,
+    pub largest_bet: u32,
+    /// The bot could no longer cover the minimum bet before every round was played
+    pub busted: bool,
+}
+
+impl SimulationReport {
+    /// Returns the net result of every round played, in the order they were played
+    pub fn round_results(&self) -> Vec<f32> {
+        self.session
+            .bankroll
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .collect()
+    }
+
+    /// Returns the mean and standard deviation of the result of a round, or None if no rounds were played
+    pub fn round_mean_and_sd(&self) -> Option<(f32, f32)> {
+        mean_and_sd(&self.round_results())
+    }
+
+    /// Returns the average number of hands played per round, more than one when the bot split or the variant deals
+    /// several hands, or None if no rounds were played
+    pub fn hands_per_round(&self) -> Option<f32> {
+        (self.session.rounds > 0)
+            .then(|| self.session.hands.hands() as f32 / self.session.rounds as f32)
+    }
+
+    /// Returns the mean and standard deviation of a round along with the hands played per round, which the statistics
+    /// per 100 hands are scaled by
+    fn per_hand_stats(&self) -> Option<(f32, f32, f32)> {
+        let (mean, sd) = self.round_mean_and_sd()?;
+        Some((mean, sd, self.hands_per_round()?))
+    }
+
+    /// Returns the average amount won per 100 hands, negative if the bot lost
+    pub fn win_rate_per_100(&self) -> Option<f32> {
+        self.per_hand_stats()
+            .map(|(mean, _, hands)| 100.0 * mean / hands)
+    }
+
+    /// Returns the standard deviation of the result of 100 hands, i.e. of the rounds it takes to play 100 hands
+    pub fn sd_per_100(&self) -> Option<f32> {
+        self.per_hand_stats()
+            .map(|(_, sd, hands)| sd * f32::sqrt(100.0 / hands))
+    }
+
+    /// Returns N0, the number of hands after which the expected result is one standard deviation, i.e. the hands it
+    /// takes for an advantage to show through the fluctuations. None if the bot did not win on average
+    pub fn n0(&self) -> Option<f32> {
+        self.per_hand_stats()
+            .filter(|(mean, _, _)| *mean > 0.0)
+            .map(|(mean, sd, hands)| hands * (sd / mean).powi(2))
+    }
+
+    /// Prints `reports` to the console as tables of their results and statistics per 100 hands, followed by their
+    /// bankrolls as sparklines drawn with unicode blocks if `unicode` is true
+    pub fn display_all(reports: &[SimulationReport], unicode: bool) {
+        let dollars =
+            |amount: Option<f32>| amount.map_or(String::from("n/a"), |a| format!("${a:.2}"));
+        println!(
+            "{:<16}{:>8}{:>12}{:>12}{:>10}{:>12}{:>8}{:>8}",
+            "Betting", "Rounds", "Net", "Avg bet", "Max bet", "Drawdown", "Capped", "Busted"
+        );
+        println!("{}", "-".repeat(86));
+        for report in reports {
+            let session = &report.session;
+            println!(
+                "{:<16}{:>8}{:>12}{:>12}{:>10}{:>12}{:>8}{:>8}",
+                report.betting,
+                session.rounds,
+                format!("${:.2}", session.net_result()),
+                dollars(session.average_bet()),
+                format!("${}", report.largest_bet),
+                format!("${:.2}", session.max_drawdown()),
+                report.capped_rounds,
+                if report.busted { "yes" } else { "no" }
+            );
+        }
+
+        println!();
+        println!(
+            "{:<16}{:>14}{:>14}{:>14}",
+            "Betting", "Win/100", "SD/100", "N0 (hands)"
+        );
+        println!("{}", "-".repeat(58));
+        for report in reports {
+            println!(
+                "{:<16}{:>14}{:>14}{:>14}",
+                report.betting,
+                dollars(report.win_rate_per_100()),
+                dollars(report.sd_per_100()),
+                report
+                    .n0()
+                    .map_or(String::from("n/a"), |n0| format!("{n0:.0}"))
+            );
+        }
+
+        println!();
+        for report in reports {
+            println!(
+                "{:<16}{}  ${:.2} -> ${:.2}",
+                report.betting,
+                sparkline(&report.session.bankroll, SPARKLINE_WIDTH, unicode),
+                report.session.bankroll[0],
+                report.session.balance()
+            );
+        }
+    }
+}
+
+/// Plays up to `rounds` rounds at a table set up by `config` with a bot sizing its bets with `sizing`, starting with
+/// the configs starting balance. The run ends early if the bot can no longer cover the minimum bet
+pub fn simulate(
+    config: &GameConfig,
+    sizing: BetSizing,
+    rounds: u32,
+) -> Result<SimulationReport, BlackjackGameError> {
+    let mut table = table_from_config(config, config.variant.build_deck(config.n_decks));
+    table.set_quiet(true);
+    let mut seat = BotSeat::new(String::from("Bot"), config.starting_balance, sizing);
+
+    let mut busted = false;
+    for _ in 0..rounds {
+        if !seat.play_round(&mut table)? {
+            busted = true;
+            break;
+        }
+    }
+    Ok(SimulationReport {
+        busted,
+        ..seat.report()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::betting::BetRamp;
+    use crate::testing::stacked_table;
+    use crate::variant::HouseRules;
+    use crate::{parse_cards, Deck};
+
+    #[test]
+    fn bot_surrenders_hard_16_against_a_ten_when_offered() {
+        let config = GameConfig {
+            rules: HouseRules {
+                late_surrender: true,
+                ..HouseRules::default()
+            },
+            ..GameConfig::default()
+        };
+        // The player is dealt 16 against the dealers ten and seven
+        let (mut table, mut player) = stacked_table("HK S7 D6 C10", &config);
+        table.deal_hand(&mut player);
+        let options = table.playing_options(&player);
+        assert_eq!(
+            options[&bot_option(&table, &player, &options)],
+            Action::Surrender
+        );
+    }
+
+    #[test]
+    fn bot_ramps_up_its_bet_at_a_positive_count() {
+        let config = GameConfig {
+            n_decks: 1,
+            ..GameConfig::default()
+        };
+        // Every card of the first round is a low card, so the count is high for the second round
+        let cards = parse_cards("H2 S3 D4 C5 H6 S2 D3 C4 H5 S6 C2 H3").unwrap();
+        let deck = Deck::with_prefix(cards, config.n_decks, Some(1)).unwrap();
+        let mut table = table_from_config(&config, deck);
+        table.set_quiet(true);
+        let ramp = BetSizing::Ramp {
+            ramp: BetRamp::spread(8),
+            unit: 5,
+        };
+        let mut bot = BotSeat::new(String::from("Bot"), 500.0, ramp);
+
+        assert!(bot.play_round(&mut table).unwrap());
+        assert_eq!(bot.largest_bet, 5);
+        assert!(table.true_count() >= 5.0);
+        assert!(bot.play_round(&mut table).unwrap());
+        assert_eq!(bot.largest_bet, 40);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::action::Action;
+use crate::Card;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Returns the value of the dealers up card for the purpose of looking up basic strategy, aces are counted as 11
+fn dealers_up_value(up_card: &Card) -> u8 {
+    if up_card.rank == "A" {
+        11
+    } else {
+        up_card.val
+    }
+}
+
+/// Returns whether basic strategy splits a pair of `pair_rank`'s against a dealers up card of value `dealer`.
+/// The chart assumes a multi-deck game where the dealer stands on all 17s and doubling after splitting is not allowed.
+fn should_split(pair_rank: &str, dealer: u8) -> bool {
+    match pair_rank {
+        "A" | "8" => true,
+        "9" => matches!(dealer, 2..=6 | 8 | 9),
+        "7" => dealer <= 7,
+        "6" => (3..=6).contains(&dealer),
+        "2" | "3" => (4..=7).contains(&dealer),
+        _ => false,
+    }
+}
+
+/// Returns whether basic strategy surrenders a hard hand with total `total` against a dealers up card of value `dealer`,
+/// when late surrender is offered
+fn should_surrender(total: u8, dealer: u8) -> bool {
+    match total {
+        16 => dealer >= 9,
+        15 => dealer == 10,
+        _ => false,
+    }
+}
+
+/// Returns the basic strategy play for a soft hand with total `total` against a dealers up card of value `dealer`
+fn soft_total_play(total: u8, dealer: u8) -> Action {
+    match total {
+        13 | 14 if (5..=6).contains(&dealer) => Action::DoubleDown,
+        15 | 16 if (4..=6).contains(&dealer) => Action::DoubleDown,
+        17 if (3..=6).contains(&dealer) => Action::DoubleDown,
+        18 if (3..=6).contains(&dealer) => Action::DoubleDown,
+        18 if dealer >= 9 => Action::Hit,
+        18 => Action::Stand,
+        t if t >= 19 => Action::Stand,
+        _ => Action::Hit,
+    }
+}
+
+/// Returns the basic strategy play for a hard hand with total `total` against a dealers up card of value `dealer`
+fn hard_total_play(total: u8, dealer: u8) -> Action {
+    match total {
+        9 if (3..=6).contains(&dealer) => Action::DoubleDown,
+        10 if dealer <= 9 => Action::DoubleDown,
+        11 if dealer <= 10 => Action::DoubleDown,
+        12 if (4..=6).contains(&dealer) => Action::Stand,
+        13..=16 if dealer <= 6 => Action::Stand,
+        t if t >= 17 => Action::Stand,
+        _ => Action::Hit,
+    }
+}
+
+/// Returns the Hi-Lo count value of `card`, +1 for 2 to 6, 0 for 7 to 9 and -1 for tens and aces
+pub fn hi_lo_value(card: &Card) -> i32 {
+    match card.val {
+        2..=6 => 1,
+        7..=9 => 0,
+        _ => -1,
+    }
+}
+
+/// Computes the basic strategy play for the hand `hand` with possible values `hand_value` against the dealers up card `up_card`.
+/// Only options contained in `options` are returned, so if the optimal play is not available i.e. doubling down
+/// on a hand that does not allow it, the next best play is returned instead.
+pub fn basic_strategy_option(
+    hand: &[Rc<Card>],
+    hand_value: &[u8],
+    up_card: &Card,
+    options: &HashMap<i32, Action>,
+) -> Action {
+    let dealer = dealers_up_value(up_card);
+    let is_available = |play: Action| options.values().any(|o| *o == play);
+
+    if is_available(Action::Split) && should_split(hand[0].rank, dealer) {
+        return Action::Split;
+    }
+
+    let soft = hand_value.len() == 2 && hand_value[1] <= 21;
+    if !soft && is_available(Action::Surrender) && should_surrender(hand_value[0], dealer) {
+        return Action::Surrender;
+    }
+
+    let play = if soft {
+        soft_total_play(hand_value[1], dealer)
+    } else {
+        hard_total_play(hand_value[0], dealer)
+    };
+
+    match play {
+        // Soft 18 stands rather than hitting when doubling is not an option
+        Action::DoubleDown
+            if !is_available(play) && hand_value.len() == 2 && hand_value[1] == 18 =>
+        {
+            Action::Stand
+        }
+        Action::DoubleDown if !is_available(play) => Action::Hit,
+        _ => play,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+
+    /// Returns the basic strategy play for the hard hand `cards` against the up card `up_card` with `options` available
+    fn play(cards: &str, up_card: &str, options: &[Action]) -> Action {
+        let hand: Vec<Rc<Card>> = parse_cards(cards)
+            .unwrap()
+            .into_iter()
+            .map(Rc::new)
+            .collect();
+        let total = hand.iter().map(|c| c.val).sum();
+        let up_card = &parse_cards(up_card).unwrap()[0];
+        let options = (1..).zip(options.iter().copied()).collect();
+        basic_strategy_option(&hand, &[total], up_card, &options)
+    }
+
+    #[test]
+    fn late_surrender_gives_up_hard_15_and_16_against_the_strongest_up_cards() {
+        let options = [Action::Stand, Action::Hit, Action::Surrender];
+        assert_eq!(play("HK D6", "C9", &options), Action::Surrender);
+        assert_eq!(play("HK D6", "C10", &options), Action::Surrender);
+        assert_eq!(play("HK D6", "CA", &options), Action::Surrender);
+        assert_eq!(play("H9 D7", "CQ", &options), Action::Surrender);
+        assert_eq!(play("HK D5", "CK", &options), Action::Surrender);
+        assert_eq!(play("HK D5", "C9", &options), Action::Hit);
+        assert_eq!(play("HK D6", "C8", &options), Action::Hit);
+        assert_eq!(play("HK D7", "CA", &options), Action::Stand);
+    }
+
+    #[test]
+    fn hard_16_hits_when_surrender_is_not_offered() {
+        assert_eq!(
+            play("HK D6", "C10", &[Action::Stand, Action::Hit]),
+            Action::Hit
+        );
+    }
+}
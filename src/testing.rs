@@ -0,0 +1,49 @@
+use crate::action::Action;
+use crate::config::GameConfig;
+use crate::console::{player::ConsolePlayer, table::ConsoleBlackjackTable};
+use crate::history::{HandHistory, HandResult};
+use crate::{parse_cards, table_from_config, BlackjackTable, Deck};
+
+/// Returns a quiet table set up by `config` dealing `cards` first, and a player who has bet $10 on it
+pub fn stacked_table(cards: &str, config: &GameConfig) -> (ConsoleBlackjackTable, ConsolePlayer) {
+    let deck = Deck::with_prefix(parse_cards(cards).unwrap(), config.n_decks, Some(1)).unwrap();
+    let mut table = table_from_config(config, deck);
+    table.set_quiet(true);
+    let mut player = ConsolePlayer::new(String::from("Player"), 500.0);
+    table.place_bet(&mut player, 10.0).unwrap();
+    (table, player)
+}
+
+/// Plays `action` on `player`'s current hand. The function panics if `action` is not one of the players options
+pub fn play_action(table: &mut ConsoleBlackjackTable, player: &mut ConsolePlayer, action: Action) {
+    let options = table.playing_options(player);
+    let option = options
+        .iter()
+        .find_map(|(i, o)| (*o == action).then_some(*i))
+        .unwrap_or_else(|| panic!("{action:?} should be an option"));
+    table.play_option(player, &options, option).unwrap();
+}
+
+/// Deals the round and plays `actions` in order, then finishes the round and returns its history
+pub fn play_round(
+    table: &mut ConsoleBlackjackTable,
+    player: &mut ConsolePlayer,
+    actions: &[Action],
+) -> HandHistory {
+    table.deal_hand(player);
+    for action in actions {
+        play_action(table, player, *action);
+    }
+    assert!(player.turn_is_over());
+    table.finish_hand(player);
+    table.take_hand_history().unwrap()
+}
+
+/// Returns the result and net of every settled hand in `history`
+pub fn results(history: &HandHistory) -> Vec<(HandResult, f32)> {
+    history
+        .settlements
+        .iter()
+        .map(|s| (s.result, s.net))
+        .collect()
+}
@@ -0,0 +1,372 @@
+use crate::history::HandResult;
+use crate::{Card, Deck};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::rc::Rc;
+
+/// The game of blackjack played at a table, every variant other than `Standard` changes some of the rules of the game
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameVariant {
+    #[default]
+    Standard,
+    /// Spanish 21 is played without the ten pip cards. Late surrender is offered, the player may double down on any
+    /// number of cards and then rescue the doubled hand, and bonuses are paid on 21s of five or more cards, 6-7-8 and 7-7-7
+    Spanish21,
+    /// Blackjack Switch, the player plays two hands of equal bets and may switch the second cards of the hands before
+    /// playing them. Blackjack pays even money and a dealer 22 pushes every hand that is not a blackjack
+    Switch,
+    /// Double Exposure, both of the dealers cards are dealt face up. Blackjack pays even money and the dealer wins every tie,
+    /// except against a players blackjack
+    DoubleExposure,
+    /// Free Bet Blackjack, hard 9, 10 and 11 are doubled for free and every pair other than tens is split for free, with the
+    /// house funding the extra bet. A dealer 22 pushes every hand that is not a blackjack
+    FreeBet,
+    /// Pontoon, the British game where the player twists, sticks or buys another card with both of the dealers cards
+    /// hidden. The player must have at least 15 to stick, a pontoon pays 2:1, a five card trick beats every hand other
+    /// than a pontoon, and the dealer wins every tie
+    Pontoon,
+}
+
+/// The rank of a finished hand used to settle it against the dealers hand, a higher rank beats a lower rank.
+/// A busted hand loses to every other hand so its total only matters to variants where a dealer 22 pushes
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum HandRank {
+    Bust(u8),
+    Total(u8),
+    FiveCardTrick,
+    Natural,
+}
+
+impl GameVariant {
+    /// Builds a shoe of `n_decks` decks with the composition used by the variant
+    pub fn build_deck(&self, n_decks: usize) -> Deck {
+        match self {
+            GameVariant::Standard => Deck::new(n_decks),
+            GameVariant::Spanish21 => Deck::new_spanish(n_decks),
+            GameVariant::Switch
+            | GameVariant::DoubleExposure
+            | GameVariant::FreeBet
+            | GameVariant::Pontoon => Deck::new(n_decks),
+        }
+    }
+
+    /// Returns the number of hands the player is dealt for every bet they place
+    pub fn hands_per_bet(&self) -> usize {
+        match self {
+            GameVariant::Switch => 2,
+            _ => 1,
+        }
+    }
+
+    /// Returns what a players blackjack pays, either the variants own fixed odds or the payout of the house rules
+    pub fn blackjack_payout(&self) -> VariantPayout {
+        match self {
+            GameVariant::Switch | GameVariant::DoubleExposure => VariantPayout::Fixed(1.0),
+            GameVariant::Pontoon => VariantPayout::Fixed(2.0),
+            _ => VariantPayout::HouseRules,
+        }
+    }
+
+    /// Returns true if insurance is offered when the dealer shows an ace. Blackjack Switch deals two hands, the dealers
+    /// cards are no secret in Double Exposure and Pontoon has no insurance
+    pub fn offers_insurance(&self) -> bool {
+        matches!(
+            self,
+            GameVariant::Standard | GameVariant::Spanish21 | GameVariant::FreeBet
+        )
+    }
+
+    /// Returns true if the dealers hole card is dealt face up
+    pub fn dealer_cards_exposed(&self) -> bool {
+        *self == GameVariant::DoubleExposure
+    }
+
+    /// Returns true if both of the dealers cards, not only the hole card, are hidden while the player acts
+    pub fn dealer_cards_hidden(&self) -> bool {
+        *self == GameVariant::Pontoon
+    }
+
+    /// Returns the lowest total the player may stand on
+    pub fn minimum_stand(&self) -> u8 {
+        match self {
+            GameVariant::Pontoon => 15,
+            _ => 0,
+        }
+    }
+
+    /// Returns the rank of the finished hand `hand` with value `total`, where `natural` should be true if the hand is a
+    /// natural blackjack i.e. its first two cards dealt were an ace and a ten valued card
+    pub fn hand_rank(&self, hand: &[Rc<Card>], total: u8, natural: bool) -> HandRank {
+        match self {
+            _ if total > 21 => HandRank::Bust(total),
+            _ if natural => HandRank::Natural,
+            GameVariant::Pontoon if hand.len() >= 5 => HandRank::FiveCardTrick,
+            _ => HandRank::Total(total),
+        }
+    }
+
+    /// Returns the result of a players hand of rank `player` against the dealers hand of rank `dealer`
+    pub fn hand_result(&self, player: HandRank, dealer: HandRank) -> HandResult {
+        match (self, player, dealer) {
+            (_, HandRank::Bust(_), _) => HandResult::Lost,
+            (GameVariant::Switch | GameVariant::FreeBet, _, HandRank::Bust(22))
+                if player != HandRank::Natural =>
+            {
+                HandResult::Pushed
+            }
+            (_, _, HandRank::Bust(_)) => HandResult::Won,
+            _ if player > dealer => HandResult::Won,
+            // The dealer wins every tie in Pontoon, and every tie other than two blackjacks in Double Exposure
+            (GameVariant::Pontoon, _, _) => HandResult::Lost,
+            (GameVariant::DoubleExposure, _, _)
+                if player == dealer && player != HandRank::Natural =>
+            {
+                HandResult::Lost
+            }
+            _ if player == dealer => HandResult::Pushed,
+            _ => HandResult::Lost,
+        }
+    }
+
+    /// Returns the payout multiplier and name of any bonus paid on a winning hand `hand` with value `total`, or None if
+    /// the hand does not qualify for a bonus. Doubled hands never qualify for a Spanish 21 bonus
+    pub fn bonus(
+        &self,
+        hand: &[Rc<Card>],
+        total: u8,
+        doubled: bool,
+    ) -> Option<(f32, &'static str)> {
+        if *self == GameVariant::Pontoon && hand.len() >= 5 && total <= 21 {
+            return Some((2.0, "five card trick"));
+        }
+        if *self != GameVariant::Spanish21 || total != 21 || doubled {
+            return None;
+        }
+
+        let suited = hand.iter().all(|c| c.suit == hand[0].suit);
+        let suited_payout = if suited && hand[0].suit == "S" {
+            3.0
+        } else if suited {
+            2.0
+        } else {
+            1.5
+        };
+        let mut ranks: Vec<&str> = hand.iter().map(|c| c.rank).collect();
+        ranks.sort();
+
+        match (ranks.as_slice(), hand.len()) {
+            (["6", "7", "8"], _) => Some((suited_payout, "6-7-8")),
+            (["7", "7", "7"], _) => Some((suited_payout, "7-7-7")),
+            (_, 5) => Some((1.5, "five card 21")),
+            (_, 6) => Some((2.0, "six card 21")),
+            (_, n) if n >= 7 => Some((3.0, "seven card 21")),
+            _ => None,
+        }
+    }
+}
+
+/// How the dealer receives their second card
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HoleCardRule {
+    /// The dealer is dealt a hole card and peeks for blackjack before the player acts
+    #[default]
+    Peek,
+    /// European no hole card, the dealer draws their second card after the player has acted and every bet, including
+    /// doubles and splits, is lost to a dealer blackjack
+    NoHoleCard,
+    /// European no hole card where only the original bets are lost to a dealer blackjack, doubles and splits are returned
+    NoHoleCardObo,
+}
+
+impl HoleCardRule {
+    /// Returns true if the dealer draws their second card after the player has acted
+    pub fn deals_hole_card_late(&self) -> bool {
+        *self != HoleCardRule::Peek
+    }
+}
+
+/// What a players blackjack pays in a variant
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VariantPayout {
+    /// The payout of the house rules, 3:2 unless the house pays 6:5
+    HouseRules,
+    /// A fixed multiple of the bet, whatever the house rules
+    Fixed(f32),
+}
+
+/// What a players blackjack pays in the variants that pay 3:2
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlackjackPayout {
+    #[default]
+    #[serde(rename = "3:2")]
+    ThreeToTwo,
+    #[serde(rename = "6:5")]
+    SixToFive,
+}
+
+impl BlackjackPayout {
+    /// Returns the multiple of the bet the payout pays
+    pub fn multiplier(&self) -> f32 {
+        match self {
+            BlackjackPayout::ThreeToTwo => 1.5,
+            BlackjackPayout::SixToFive => 1.2,
+        }
+    }
+}
+
+/// The house rules a table deals with on top of the rules of its variant
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct HouseRules {
+    /// The dealer hits a soft 17 rather than standing on it
+    pub dealer_hits_soft_17: bool,
+    /// What a blackjack pays, only changes the payout of the variants that pay 3:2
+    pub blackjack_pays: BlackjackPayout,
+    /// The player may surrender their first two cards in Standard and Double Exposure, the other variants have their own rules
+    pub late_surrender: bool,
+}
+
+impl HouseRules {
+    /// Returns the multiple of the bet a players blackjack pays playing `variant` under these rules
+    pub fn blackjack_payout(&self, variant: GameVariant) -> f32 {
+        match variant.blackjack_payout() {
+            VariantPayout::HouseRules => self.blackjack_pays.multiplier(),
+            VariantPayout::Fixed(payout) => payout,
+        }
+    }
+}
+
+impl Display for HoleCardRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            HoleCardRule::Peek => "Peek",
+            HoleCardRule::NoHoleCard => "ENHC",
+            HoleCardRule::NoHoleCardObo => "ENHC (OBO)",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Display for GameVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            GameVariant::Standard => "Standard",
+            GameVariant::Spanish21 => "Spanish 21",
+            GameVariant::Switch => "Blackjack Switch",
+            GameVariant::DoubleExposure => "Double Exposure",
+            GameVariant::FreeBet => "Free Bet",
+            GameVariant::Pontoon => "Pontoon",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Display for BlackjackPayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let payout = match self {
+            BlackjackPayout::ThreeToTwo => "3:2",
+            BlackjackPayout::SixToFive => "6:5",
+        };
+        write!(f, "{payout}")
+    }
+}
+
+impl Display for HouseRules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let soft_17 = if self.dealer_hits_soft_17 {
+            "H17"
+        } else {
+            "S17"
+        };
+        write!(f, "{soft_17}, blackjack pays {}", self.blackjack_pays)?;
+        if self.late_surrender {
+            write!(f, ", late surrender")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+
+    /// Returns the cards of `cards` as a hand
+    fn hand(cards: &str) -> Vec<Rc<Card>> {
+        parse_cards(cards)
+            .unwrap()
+            .into_iter()
+            .map(Rc::new)
+            .collect()
+    }
+
+    #[test]
+    fn spanish_21_pays_bonuses_on_21s_of_five_or_more_cards() {
+        let bonus = |cards| GameVariant::Spanish21.bonus(&hand(cards), 21, false);
+        assert_eq!(bonus("H2 D3 C4 S5 H7"), Some((1.5, "five card 21")));
+        assert_eq!(bonus("H2 D3 C4 S5 H3 D4"), Some((2.0, "six card 21")));
+        assert_eq!(bonus("HA D2 C3 S4 H2 D3 C6"), Some((3.0, "seven card 21")));
+        assert_eq!(bonus("H2 D3 C4 SK"), None);
+        assert_eq!(
+            GameVariant::Standard.bonus(&hand("H2 D3 C4 S5 H7"), 21, false),
+            None
+        );
+    }
+
+    #[test]
+    fn spanish_21_pays_more_on_a_suited_6_7_8_or_7_7_7() {
+        let bonus = |cards| GameVariant::Spanish21.bonus(&hand(cards), 21, false);
+        assert_eq!(bonus("H8 D6 C7"), Some((1.5, "6-7-8")));
+        assert_eq!(bonus("H8 H6 H7"), Some((2.0, "6-7-8")));
+        assert_eq!(bonus("S6 S7 S8"), Some((3.0, "6-7-8")));
+        assert_eq!(bonus("H7 D7 C7"), Some((1.5, "7-7-7")));
+        assert_eq!(bonus("D7 D7 D7"), Some((2.0, "7-7-7")));
+        assert_eq!(bonus("S7 S7 S7"), Some((3.0, "7-7-7")));
+    }
+
+    #[test]
+    fn spanish_21_pays_no_bonus_on_a_doubled_hand() {
+        let spanish = GameVariant::Spanish21;
+        assert_eq!(spanish.bonus(&hand("S6 S7 S8"), 21, true), None);
+        assert_eq!(spanish.bonus(&hand("H2 D3 C4 S5 H7"), 21, true), None);
+    }
+
+    #[test]
+    fn switch_and_free_bet_push_a_dealer_22() {
+        for variant in [GameVariant::Switch, GameVariant::FreeBet] {
+            let result = |player| variant.hand_result(player, HandRank::Bust(22));
+            assert_eq!(result(HandRank::Total(18)), HandResult::Pushed);
+            assert_eq!(result(HandRank::Natural), HandResult::Won);
+            assert_eq!(result(HandRank::Bust(23)), HandResult::Lost);
+            assert_eq!(
+                variant.hand_result(HandRank::Total(18), HandRank::Bust(23)),
+                HandResult::Won
+            );
+        }
+        assert_eq!(
+            GameVariant::Standard.hand_result(HandRank::Total(18), HandRank::Bust(22)),
+            HandResult::Won
+        );
+    }
+
+    #[test]
+    fn switch_and_double_exposure_pay_blackjack_even_money() {
+        let rules = HouseRules::default();
+        assert_eq!(rules.blackjack_payout(GameVariant::Switch), 1.0);
+        assert_eq!(rules.blackjack_payout(GameVariant::DoubleExposure), 1.0);
+        assert_eq!(rules.blackjack_payout(GameVariant::Standard), 1.5);
+        assert_eq!(rules.blackjack_payout(GameVariant::FreeBet), 1.5);
+    }
+
+    #[test]
+    fn double_exposure_loses_ties_other_than_two_naturals() {
+        let result = |rank| GameVariant::DoubleExposure.hand_result(rank, rank);
+        assert_eq!(result(HandRank::Total(17)), HandResult::Lost);
+        assert_eq!(result(HandRank::Total(21)), HandResult::Lost);
+        assert_eq!(result(HandRank::Natural), HandResult::Pushed);
+        assert_eq!(
+            GameVariant::Standard.hand_result(HandRank::Total(17), HandRank::Total(17)),
+            HandResult::Pushed
+        );
+    }
+}